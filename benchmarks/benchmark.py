@@ -0,0 +1,99 @@
+"""Times sstn on a JSONL corpus and reports the time per document.
+
+Two paths are timed: `normalize_jsonl_file` on the whole file, and
+`normalize_text` called once per document, which pays for the config
+conversion and the pipeline lookup on every call.
+
+The Dolma shard from the README:
+
+    python benchmarks/benchmark.py cc_en_middle-0577.json.gz --workers 16
+
+The synthetic corpus (written first, then timed):
+
+    python benchmarks/benchmark.py corpus.jsonl.gz --synthetic \
+        --vocabulary ~/.cargo/registry/src/*/rust-stemmers-1.2.0/test_data/voc_en.txt
+
+Run it once per build to compare them, the corpus is only read.
+"""
+
+import argparse
+import gzip
+import json
+import os
+import random
+import tempfile
+import time
+
+import sstn
+
+# Spread over the Zipfian head so that stopwords are as frequent as in real text
+STOPWORDS = "the of and to a in is that for it as was with be by on not he this are or his from at which".split()
+
+
+def write_synthetic(path, vocabulary, documents, seed):
+    """Documents of 100 to 600 words drawn with Zipfian weights, 5% of them capitalized"""
+    rng = random.Random(seed)
+    with open(vocabulary) as f:
+        words = [word.strip() for word in f if word.strip()]
+    rng.shuffle(words)
+    words = STOPWORDS + words[:20000]
+    weights = [1 / (rank + 1) for rank in range(len(words))]
+
+    with gzip.open(path, "wt") as f:
+        for _ in range(documents):
+            text = rng.choices(words, weights, k=rng.randint(100, 600))
+            text = [word.capitalize() if rng.random() < 0.05 else word for word in text]
+            f.write(json.dumps({"text": " ".join(text) + "."}) + "\n")
+
+
+def read_texts(path, limit):
+    opener = gzip.open if path.endswith(".gz") else open
+    texts = []
+    with opener(path, "rt") as f:
+        for line in f:
+            if len(texts) == limit:
+                break
+            if line.strip():
+                texts.append(json.loads(line).get("text", ""))
+    return texts
+
+
+def report(name, documents, seconds):
+    print(f"{name}: {documents} documents in {seconds:.2f} s, {seconds / documents * 1e6:.1f} µs per document")
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__, formatter_class=argparse.RawDescriptionHelpFormatter)
+    parser.add_argument("corpus", help="JSONL file with a `text` field, gzipped or not")
+    parser.add_argument("--workers", type=int, default=16)
+    parser.add_argument("--texts", type=int, default=10000, help="documents timed one by one with normalize_text")
+    parser.add_argument("--synthetic", action="store_true", help="write the synthetic corpus to CORPUS first")
+    parser.add_argument("--vocabulary", help="word list of the synthetic corpus, one word per line")
+    parser.add_argument("--documents", type=int, default=50000, help="documents in the synthetic corpus")
+    parser.add_argument("--seed", type=int, default=0)
+    args = parser.parse_args()
+
+    if args.synthetic:
+        if args.vocabulary is None:
+            parser.error("--synthetic requires --vocabulary")
+        write_synthetic(args.corpus, args.vocabulary, args.documents, args.seed)
+
+    opener = gzip.open if args.corpus.endswith(".gz") else open
+    with opener(args.corpus, "rt") as f:
+        documents = sum(1 for line in f if line.strip())
+
+    with tempfile.TemporaryDirectory() as output:
+        start = time.perf_counter()
+        sstn.normalize_jsonl_file(args.corpus, os.path.join(output, "normalized.jsonl"), "text", workers=args.workers)
+        report("normalize_jsonl_file", documents, time.perf_counter() - start)
+
+    texts = read_texts(args.corpus, args.texts)
+    config = sstn.NormalizerConfig()
+    start = time.perf_counter()
+    for text in texts:
+        sstn.normalize_text(text, config)
+    report("normalize_text", len(texts), time.perf_counter() - start)
+
+
+if __name__ == "__main__":
+    main()
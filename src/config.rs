@@ -0,0 +1,190 @@
+//! config.rs
+//!
+//! Provides the normalizer configuration as received from the Python API.
+//! The fields mirror `sstn.NormalizerConfig`, which owns the defaults and
+//! hands us a plain dict, so everything here is kept as simple values.
+//! Validation happens when the config is compiled into a `Pipeline`.
+
+use std::collections::HashMap;
+use pyo3::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, FromPyObject)]
+#[pyo3(from_item_all)]
+pub struct NormalizerConfig {
+    /// Strip markup and decode HTML entities before anything else
+    pub strip_html: bool,
+    /// "ascii" (transliterate, split on non-alphanumerics) or "unicode" (UAX #29 words)
+    pub mode: String,
+    /// Language used to select the stemmer and the stopword list
+    pub language: String,
+    /// "snowball", "none", "s", "lovins", "lancaster", "krovetz" or "lemmatizer"
+    pub stemmer: String,
+    /// Word → stem overrides (the word itself to keep it unstemmed)
+    pub stem_exceptions: HashMap<String, String>,
+    /// Per-thread LRU cache of stems, 0 disables it
+    pub stem_cache_size: usize,
+    /// Write a stem → most frequent word sidecar next to each normalized file
+    pub surface_forms: bool,
+    /// Write the term and document frequency of every output token
+    pub vocabulary: bool,
+    /// Tokens seen less often are left out of the vocabulary
+    pub vocabulary_min_count: u64,
+    /// Unicode normalization form applied first ("nfc", "nfd", "nfkc", "nfkd")
+    pub unicode_form: Option<String>,
+    /// Remove diacritics (café → cafe) without transliterating
+    pub strip_diacritics: bool,
+    /// Transliteration backend used in ascii mode ("unidecode" or "deunicode")
+    pub transliterator: String,
+    /// Ordered (pattern, replacement) regex rules
+    pub rewrite_rules: Vec<(String, String)>,
+    /// "raw" (before anything else) or "ascii" (transliterated buffer, before splitting)
+    pub rewrite_stage: String,
+    /// Token filters: words outside [min_token_length, max_token_length] are dropped
+    pub min_token_length: usize,
+    pub max_token_length: Option<usize>,
+    /// What to do with numbers: "drop", "keep" or "placeholder"
+    pub numbers: String,
+    pub number_placeholder: String,
+    /// Drop words mixing letters and digits
+    pub drop_alphanumeric: bool,
+    /// Identifier splitting rules
+    pub split_camel_case: bool,
+    pub split_acronyms: bool,
+    pub split_digits: bool,
+    pub split_snake_case: bool,
+    /// Dictionary segmentation for CJK/Thai ("auto", "always" or "off")
+    pub segmentation: String,
+    /// Pick the stemmer and stopwords from the detected language of each document
+    pub auto_language: bool,
+    /// What to do with documents whose normalized text was seen before ("off", "drop" or "flag")
+    pub dedup: String,
+    /// JSONL field receiving the first occurrence of a repeated document, in "flag" mode
+    pub duplicate_field: String,
+    /// JSONL field receiving the hash of the normalized text
+    pub hash_field: Option<String>,
+    /// What makes documents duplicates: "exact" (same text) or "minhash" (same LSH cluster)
+    pub dedup_method: String,
+    /// MinHash permutations, 0 disables signatures
+    pub minhash_permutations: usize,
+    /// Words per shingle
+    pub shingle_size: usize,
+    pub minhash_seed: u64,
+    /// LSH bands (must divide the permutations), 0 disables clustering
+    pub lsh_bands: usize,
+    /// JSONL field receiving the MinHash signature
+    pub minhash_field: Option<String>,
+    /// JSONL field receiving the LSH cluster ID
+    pub cluster_field: Option<String>,
+    /// JSONL field receiving the 64-bit SimHash
+    pub simhash_field: Option<String>,
+    /// `min_<metric>` / `max_<metric>` → threshold, e.g. "min_words" → 50
+    pub quality_filters: HashMap<String, f64>,
+    /// What to do with documents failing a filter: "drop" or "annotate"
+    pub quality_action: String,
+    /// JSONL field receiving the quality scores
+    pub quality_field: Option<String>,
+    /// JSONL field receiving the detected language (ISO 639-3)
+    pub language_field: Option<String>,
+    /// JSONL field receiving the detection confidence
+    pub language_confidence_field: Option<String>,
+    /// Entity kind ("url", "email", ...) → placeholder token, None drops the entity
+    pub entities: HashMap<String, Option<String>>,
+    /// Protected term → emitted form (the term itself to keep it verbatim)
+    pub protected_tokens: HashMap<String, String>,
+    /// Match protected terms ignoring ASCII case
+    pub protected_case_insensitive: bool,
+    /// TSV file of term<TAB>canonical pairs, applied before stemming
+    pub synonyms_file: Option<String>,
+    /// File with one multiword expression per line, merged into single tokens
+    pub phrases_file: Option<String>,
+    /// "before_stemming" (new_york) or "after_stemming" (machin_learn)
+    pub phrase_stage: String,
+    /// Joins the words of a merged phrase
+    pub phrase_separator: String,
+    /// Smallest and largest n of the word n-grams, (1, 1) emits the tokens as-is
+    pub ngram_range: (usize, usize),
+    /// Joins the tokens of an n-gram
+    pub ngram_separator: String,
+    /// JSONL array field receiving the n-grams, None replaces the text with them
+    pub ngram_field: Option<String>,
+    /// Buckets of the hashing-trick vectors, 0 disables them
+    pub hashing_features: usize,
+    pub hashing_seed: u64,
+    /// "count" or "tfidf"
+    pub hashing_weights: String,
+    /// Vocabulary file (token<TAB>tf<TAB>df) the IDF weights are computed from
+    pub idf_file: Option<String>,
+    /// Scale vectors to unit length
+    pub hashing_normalize: bool,
+    /// JSONL field receiving the vectors as [bucket, weight] pairs
+    pub hashing_field: Option<String>,
+    /// Write the vectors to a binary file next to each normalized file
+    pub hashing_file: bool,
+}
+
+/// The defaults of `sstn.NormalizerConfig`, for the tests
+#[cfg(test)]
+impl Default for NormalizerConfig {
+    fn default() -> Self {
+        NormalizerConfig {
+            strip_html: false,
+            mode: "ascii".to_string(),
+            language: "english".to_string(),
+            stemmer: "snowball".to_string(),
+            stem_exceptions: HashMap::new(),
+            stem_cache_size: 0,
+            surface_forms: false,
+            vocabulary: false,
+            vocabulary_min_count: 1,
+            unicode_form: None,
+            strip_diacritics: false,
+            transliterator: "unidecode".to_string(),
+            rewrite_rules: Vec::new(),
+            rewrite_stage: "raw".to_string(),
+            min_token_length: 2,
+            max_token_length: None,
+            numbers: "drop".to_string(),
+            number_placeholder: "<num>".to_string(),
+            drop_alphanumeric: false,
+            split_camel_case: true,
+            split_acronyms: false,
+            split_digits: false,
+            split_snake_case: false,
+            segmentation: "auto".to_string(),
+            auto_language: false,
+            dedup: "off".to_string(),
+            duplicate_field: "duplicate_of".to_string(),
+            hash_field: None,
+            dedup_method: "exact".to_string(),
+            minhash_permutations: 0,
+            shingle_size: 5,
+            minhash_seed: 0,
+            lsh_bands: 0,
+            minhash_field: None,
+            cluster_field: None,
+            simhash_field: None,
+            quality_filters: HashMap::new(),
+            quality_action: "drop".to_string(),
+            quality_field: None,
+            language_field: None,
+            language_confidence_field: None,
+            entities: HashMap::new(),
+            protected_tokens: HashMap::new(),
+            protected_case_insensitive: false,
+            synonyms_file: None,
+            phrases_file: None,
+            phrase_stage: "before_stemming".to_string(),
+            phrase_separator: "_".to_string(),
+            ngram_range: (1, 1),
+            ngram_separator: "_".to_string(),
+            ngram_field: None,
+            hashing_features: 0,
+            hashing_seed: 0,
+            hashing_weights: "count".to_string(),
+            idf_file: None,
+            hashing_normalize: false,
+            hashing_field: None,
+            hashing_file: false,
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! counts.rs
+//!
+//! Provides the string-keyed counters behind the corpus statistics written
+//! next to normalized files (vocabulary, surface forms).
+//! Each worker counts on its own, the counts are merged once at the end.
+
+use std::collections::HashMap;
+
+/// Values keyed by string, updated in place
+pub struct Counter<V> {
+    map: HashMap<String, V>,
+}
+
+impl<V> Default for Counter<V> {
+    fn default() -> Self {
+        Counter { map: HashMap::new() }
+    }
+}
+
+impl<V: Default> Counter<V> {
+    /// Calls `f` with the value of `key`, starting from the default for new keys
+    pub fn update(&mut self, key: &str, f: impl FnOnce(&mut V)) {
+        // Look up first, most keys have been seen already and need no allocation
+        match self.map.get_mut(key) {
+            Some(value) => f(value),
+            None => {
+                let mut value = V::default();
+                f(&mut value);
+                self.map.insert(key.to_string(), value);
+            }
+        }
+    }
+}
+
+impl<V> Counter<V> {
+    /// Merges two workers' counts, `combine` adds a value of one to the value of the other
+    pub fn merge(self, other: Counter<V>, mut combine: impl FnMut(&mut V, V)) -> Counter<V> {
+        // Merge the smaller map into the larger one
+        let (mut larger, smaller) = match self.map.len() >= other.map.len() {
+            true => (self, other),
+            false => (other, self),
+        };
+        for (key, value) in smaller.map {
+            match larger.map.get_mut(&key) {
+                Some(merged) => combine(merged, value),
+                None => { larger.map.insert(key, value); }
+            }
+        }
+        larger
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &V)> {
+        self.map.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_counts() {
+        let count = |keys: &[&str]| {
+            let mut counter = Counter::<u64>::default();
+            for key in keys {
+                counter.update(key, |count| *count += 1);
+            }
+            counter
+        };
+        let merged = count(&["a", "b", "a"]).merge(count(&["b", "c", "d", "b"]), |count, other| *count += other);
+        let mut counts: Vec<(&String, &u64)> = merged.iter().collect();
+        counts.sort_unstable();
+        let counts: Vec<(&str, u64)> = counts.into_iter().map(|(key, &count)| (key.as_str(), count)).collect();
+        assert_eq!(counts, [("a", 2), ("b", 3), ("c", 1), ("d", 1)]);
+    }
+}
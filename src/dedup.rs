@@ -0,0 +1,150 @@
+//! dedup.rs
+//!
+//! Provides deduplication of documents by the hash of their normalized text (exact)
+//! or by their MinHash cluster (near duplicates).
+//! Only hashes and the location of each document are kept, never the texts,
+//! so that repeats can be found across many large files.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Result, Write};
+use std::path::Path;
+use xxhash_rust::xxh3::xxh3_128;
+use crate::pipeline::{invalid_input, Pipeline};
+use crate::simhash::simhash;
+
+/// What happens to documents whose normalized text has been seen before
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DedupMode {
+    Off,
+    /// Repeats are left out of the output
+    Drop,
+    /// Repeats are kept, with the location of the first occurrence in a field
+    Flag,
+}
+
+impl DedupMode {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "off" => Ok(DedupMode::Off),
+            "drop" => Ok(DedupMode::Drop),
+            "flag" => Ok(DedupMode::Flag),
+            _ => Err(invalid_input(format!("Unknown dedup mode: {}", name))),
+        }
+    }
+}
+
+/// What makes two documents duplicates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DedupMethod {
+    /// Same normalized text
+    Exact,
+    /// Same MinHash LSH cluster
+    MinHash,
+}
+
+impl DedupMethod {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "exact" => Ok(DedupMethod::Exact),
+            "minhash" => Ok(DedupMethod::MinHash),
+            _ => Err(invalid_input(format!("Unknown dedup method: {}", name))),
+        }
+    }
+}
+
+/// What is kept of a normalized document
+pub struct Fingerprint {
+    pub hash: u128,
+    pub signature: Option<Vec<u64>>,
+    pub simhash: Option<u64>,
+}
+
+impl Fingerprint {
+    pub fn new(text: &str, pipeline: &Pipeline) -> Self {
+        Fingerprint {
+            hash: xxh3_128(text.as_bytes()),
+            signature: pipeline.minhash.as_ref().map(|minhash| minhash.signature(text)),
+            simhash: pipeline.simhash_field.is_some().then(|| simhash(text)),
+        }
+    }
+}
+
+/// A document: the index of its input file and its line in that file (from 1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Location {
+    pub file: usize,
+    pub line: usize,
+}
+
+impl Location {
+    /// `path:line`, as written to output fields and reports
+    pub fn describe(&self, files: &[String]) -> String {
+        format!("{}:{}", files[self.file], self.line)
+    }
+}
+
+/// Remembers every key (text hash or cluster) seen so far, across files
+pub struct Deduplicator {
+    method: DedupMethod,
+    files: Vec<String>,
+    /// Key → first occurrence and repeats
+    clusters: HashMap<u128, (Location, Vec<Location>)>,
+}
+
+impl Deduplicator {
+    pub fn new(method: DedupMethod) -> Self {
+        Deduplicator { method, files: Vec::new(), clusters: HashMap::new() }
+    }
+
+
+    /// Registers an input file, returns the index to use in its locations
+    pub fn add_file(&mut self, path: &str) -> usize {
+        self.files.push(path.to_string());
+        self.files.len() - 1
+    }
+
+    /// Records a document, returns its first occurrence when it is a repeat
+    pub fn check(&mut self, key: u128, location: Location) -> Option<Location> {
+        match self.clusters.get_mut(&key) {
+            Some((first, repeats)) => {
+                repeats.push(location);
+                Some(*first)
+            }
+            None => {
+                self.clusters.insert(key, (location, Vec::new()));
+                None
+            }
+        }
+    }
+
+    pub fn describe(&self, location: Location) -> String {
+        location.describe(&self.files)
+    }
+
+    /// Writes one `key<TAB>size<TAB>first<TAB>repeat...` line per cluster of duplicates,
+    /// in the order of their first occurrence
+    /// The key is the text hash in hex, or the MinHash cluster ID
+    pub fn write_report(&self, path: &Path) -> Result<()> {
+        let mut clusters: Vec<(&u128, &(Location, Vec<Location>))> = self.clusters
+            .iter()
+            .filter(|(_, (_, repeats))| !repeats.is_empty())
+            .collect();
+        clusters.sort_unstable_by_key(|(_, (first, _))| *first);
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        for (key, (first, repeats)) in clusters {
+            match self.method {
+                DedupMethod::Exact => write!(writer, "{:032x}", key)?,
+                DedupMethod::MinHash => write!(writer, "{}", key)?,
+            }
+            write!(writer, "\t{}\t{}", repeats.len() + 1, self.describe(*first))?;
+            for repeat in repeats {
+                write!(writer, "\t{}", self.describe(*repeat))?;
+            }
+            writeln!(writer)?;
+        }
+        writer.flush()
+    }
+}
+
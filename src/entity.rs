@@ -0,0 +1,176 @@
+//! entity.rs
+//!
+//! Provides recognizers for entities that the tokenizer would otherwise shred
+//! (URLs, emails, @mentions, #hashtags, IP addresses, dates and numbers).
+//! They run on the raw text, before transliteration and stripping, and
+//! each match is replaced by a placeholder token or dropped.
+
+use std::collections::HashMap;
+use std::io::Result;
+use regex::Regex;
+use crate::pipeline::invalid_input;
+
+/// Recognizers in priority order, earlier ones win when several match at the same position
+/// (an email contains an @mention, a date or an IP contains numbers...)
+const RECOGNIZERS: [(&str, &str); 7] = [
+    ("email", r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}"),
+    // Trailing punctuation is most likely part of the sentence, not the URL
+    ("url", r#"(?i:(?:https?|ftp)://|www\.)[^\s<>"']*[^\s<>"'.,;:!?)\]}]"#),
+    ("ip", r"\b(?:(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\.){3}(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\b"),
+    ("date", concat!(
+        r"\b\d{4}[-/.]\d{1,2}[-/.]\d{1,2}\b",
+        r"|\b\d{1,2}[-/.]\d{1,2}[-/.]\d{2,4}\b",
+        r"|(?i:\b(?:jan|feb|mar|apr|may|jun|jul|aug|sep|sept|oct|nov|dec)[a-z]*\.?\s+\d{1,2}(?:st|nd|rd|th)?,?\s+\d{4}\b)",
+        r"|(?i:\b\d{1,2}(?:st|nd|rd|th)?\s+(?:jan|feb|mar|apr|may|jun|jul|aug|sep|sept|oct|nov|dec)[a-z]*\.?,?\s+\d{4}\b)",
+    )),
+    ("mention", r"\B@\w+"),
+    ("hashtag", r"\B#\w+"),
+    ("number", r"[-+]?\b\d+(?:[.,]\d+)*\b%?"),
+];
+
+/// A chunk of text after entity recognition
+pub enum Piece<'a> {
+    /// Regular text, goes through the rest of the pipeline
+    Text(&'a str),
+    /// Emitted as-is (placeholders, protected terms), skipping stripping, stopwords and stemming
+    Token(&'a str),
+    /// Canonical words replacing the match (synonyms), separated by single spaces,
+    /// skip the tokenizer but are still stemmed
+    Canonical(&'a str),
+}
+
+pub struct EntityRecognizer {
+    regex: Regex,
+    /// Group names of the enabled recognizers, in the order they appear in the regex
+    kinds: Vec<&'static str>,
+    /// Placeholder per kind, None drops the entity
+    placeholders: HashMap<&'static str, Option<String>>,
+}
+
+impl EntityRecognizer {
+    /// Builds a recognizer from a kind → placeholder map, None when the map is empty
+    pub fn new(entities: &HashMap<String, Option<String>>) -> Result<Option<Self>> {
+        if entities.is_empty() {
+            return Ok(None);
+        }
+
+        for kind in entities.keys() {
+            if !RECOGNIZERS.iter().any(|(name, _)| name == kind) {
+                return Err(invalid_input(format!("Unknown entity: {}", kind)));
+            }
+        }
+
+        let enabled: Vec<(&'static str, &'static str)> = RECOGNIZERS
+            .into_iter()
+            .filter(|(name, _)| entities.contains_key(*name))
+            .collect();
+
+        let pattern = enabled
+            .iter()
+            .map(|(name, pattern)| format!("(?P<{}>{})", name, pattern))
+            .collect::<Vec<_>>()
+            .join("|");
+        let regex = Regex::new(&pattern)
+            .map_err(|e| invalid_input(format!("Failed to compile entity recognizers: {}", e)))?;
+
+        let kinds: Vec<&'static str> = enabled.iter().map(|(name, _)| *name).collect();
+        let placeholders = kinds.iter().map(|&kind| (kind, entities[kind].clone())).collect();
+        Ok(Some(EntityRecognizer { regex, kinds, placeholders }))
+    }
+
+    /// Splits the text into regular text and placeholder tokens
+    pub fn split<'a>(&'a self, text: &'a str) -> Vec<Piece<'a>> {
+        let mut pieces = Vec::new();
+        let mut last = 0;
+        for captures in self.regex.captures_iter(text) {
+            // Group 0 is the whole match, the recognizers follow in order
+            let Some((kind, m)) = self.kinds.iter()
+                .enumerate()
+                .find_map(|(i, kind)| captures.get(i + 1).map(|m| (*kind, m))) else { continue };
+
+            if m.start() > last {
+                pieces.push(Piece::Text(&text[last..m.start()]));
+            }
+            if let Some(placeholder) = &self.placeholders[kind] {
+                pieces.push(Piece::Token(placeholder));
+            }
+            last = m.end();
+        }
+        if last < text.len() {
+            pieces.push(Piece::Text(&text[last..]));
+        }
+        pieces
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(entities: &[(&str, Option<&str>)], text: &str) -> Vec<String> {
+        let entities = entities.iter().map(|&(kind, placeholder)| (kind.to_string(), placeholder.map(str::to_string))).collect();
+        let recognizer = EntityRecognizer::new(&entities).unwrap().unwrap();
+        recognizer
+            .split(text)
+            .into_iter()
+            .map(|piece| match piece {
+                Piece::Token(token) => format!("[{}]", token),
+                Piece::Text(chunk) | Piece::Canonical(chunk) => chunk.to_string(),
+            })
+            .collect()
+    }
+
+    const ALL: [(&str, Option<&str>); 4] = [
+        ("url", Some("<url>")),
+        ("email", Some("<email>")),
+        ("number", Some("<num>")),
+        ("date", Some("<date>")),
+    ];
+
+    #[test]
+    fn recognizes_urls() {
+        assert_eq!(split(&ALL, "see https://example.com/a?b=1 now"), ["see ", "[<url>]", " now"]);
+        assert_eq!(split(&ALL, "https://x.org is www.x.org"), ["[<url>]", " is ", "[<url>]"]);
+        assert_eq!(split(&ALL, "(https://x.org)."), ["(", "[<url>]", ")."]);
+        assert_eq!(split(&ALL, "Go to WWW.X.ORG/path, then stop"), ["Go to ", "[<url>]", ", then stop"]);
+    }
+
+    #[test]
+    fn recognizes_emails() {
+        assert_eq!(split(&ALL, "me@x.org wrote"), ["[<email>]", " wrote"]);
+        assert_eq!(split(&ALL, "write to <first.last+tag@mail.x.co.uk>."), ["write to <", "[<email>]", ">."]);
+        // The email wins over the @mention it contains
+        let entities = [("email", Some("<email>")), ("mention", Some("<user>"))];
+        assert_eq!(split(&entities, "me@x.org and @me"), ["[<email>]", " and ", "[<user>]"]);
+    }
+
+    #[test]
+    fn recognizes_numbers() {
+        assert_eq!(split(&ALL, "Pay 1,000.50 or -3%"), ["Pay ", "[<num>]", " or ", "[<num>]"]);
+        assert_eq!(split(&ALL, "42"), ["[<num>]"]);
+        assert_eq!(split(&ALL, "(7)."), ["(", "[<num>]", ")."]);
+        // Digits inside words are not numbers
+        assert_eq!(split(&ALL, "mp3 and b2b"), ["mp3 and b2b"]);
+    }
+
+    #[test]
+    fn recognizes_dates() {
+        assert_eq!(split(&ALL, "2024-01-15 and 15/01/2024"), ["[<date>]", " and ", "[<date>]"]);
+        assert_eq!(split(&ALL, "on March 3rd, 2024."), ["on ", "[<date>]", "."]);
+        assert_eq!(split(&ALL, "(3 Sept. 2024)"), ["(", "[<date>]", ")"]);
+    }
+
+    #[test]
+    fn none_drops_the_entity() {
+        let entities = [("url", None), ("number", Some("<num>"))];
+        assert_eq!(split(&entities, "see https://x.org/1 or 2"), ["see ", " or ", "[<num>]"]);
+        assert_eq!(split(&entities, "https://x.org"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn rejects_unknown_entities() {
+        let entities = HashMap::from([("phone".to_string(), None)]);
+        assert!(EntityRecognizer::new(&entities).is_err());
+        assert!(EntityRecognizer::new(&HashMap::new()).unwrap().is_none());
+    }
+}
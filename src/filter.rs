@@ -0,0 +1,86 @@
+//! filter.rs
+//!
+//! Provides the token filters applied to every word coming out of the tokenizer,
+//! before stopword removal: length bounds, numbers and words mixing letters and digits.
+//! The garbage filters (too long, mixed) look at the whole word before identifier
+//! splitting, otherwise base64 blobs would be cut into plausible camelCase parts.
+
+use std::io::Result;
+use crate::pipeline::invalid_input;
+use crate::segment::is_complex_script;
+
+/// What happens to words made only of digits
+#[derive(Debug, Clone, PartialEq)]
+pub enum Numbers {
+    Drop,
+    Keep,
+    /// Replaced by a verbatim token
+    Placeholder(String),
+}
+
+impl Numbers {
+    pub fn parse(name: &str, placeholder: &str) -> Result<Self> {
+        match name {
+            "drop" => Ok(Numbers::Drop),
+            "keep" => Ok(Numbers::Keep),
+            "placeholder" => Ok(Numbers::Placeholder(placeholder.to_string())),
+            _ => Err(invalid_input(format!("Unknown numbers policy: {}", name))),
+        }
+    }
+}
+
+/// Outcome of the filters for a single word
+#[derive(Debug, PartialEq)]
+pub enum Verdict<'a> {
+    Keep,
+    Drop,
+    /// Emit this verbatim token instead
+    Replace(&'a str),
+}
+
+pub struct TokenFilter {
+    /// Shorter words are dropped (words of CJK/Thai are exempt, they are often a single character)
+    pub min_length: usize,
+    /// Longer words are dropped
+    pub max_length: Option<usize>,
+    pub numbers: Numbers,
+    /// Drop words with both letters and digits (a1b2c3, 5ghz)
+    pub drop_mixed: bool,
+}
+
+impl TokenFilter {
+    /// Whether a word is garbage, checked before identifier splitting
+    pub fn rejects(&self, word: &str) -> bool {
+        if self.max_length.is_some_and(|max| char_len(word) > max) {
+            return true;
+        }
+        self.drop_mixed
+            && word.chars().any(char::is_numeric)
+            && word.chars().any(char::is_alphabetic)
+    }
+
+    /// Runs the number and minimum length filters on a word (or a part of an identifier)
+    pub fn check(&self, word: &str) -> Verdict<'_> {
+        if word.is_empty() {
+            return Verdict::Drop;
+        }
+
+        if word.chars().all(char::is_numeric) {
+            match &self.numbers {
+                Numbers::Drop => return Verdict::Drop,
+                Numbers::Placeholder(placeholder) => return Verdict::Replace(placeholder),
+                // Kept numbers go through the length filter like any other word
+                Numbers::Keep => {}
+            }
+        }
+
+        if char_len(word) < self.min_length && !word.chars().any(is_complex_script) {
+            return Verdict::Drop;
+        }
+        Verdict::Keep
+    }
+}
+
+pub fn char_len(word: &str) -> usize {
+    if word.is_ascii() { word.len() } else { word.chars().count() }
+}
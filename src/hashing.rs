@@ -0,0 +1,185 @@
+//! hashing.rs
+//!
+//! Provides hashing-trick feature vectors of normalized documents: each token
+//! is mapped to one of a fixed number of buckets by a seeded hash, weighted by
+//! its count or by TF-IDF, without keeping a vocabulary in memory.
+//! IDF weights come from a vocabulary file written by an earlier run
+//! (`token<TAB>tf<TAB>df` rows) and the number of documents in its `.totals` sidecar.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Result, Write};
+use std::path::Path;
+use xxhash_rust::xxh3::xxh3_64_with_seed;
+use crate::pipeline::invalid_input;
+use crate::vocab::totals_path;
+
+/// A sparse vector, (bucket, weight) sorted by bucket
+pub type SparseVector = Vec<(u32, f32)>;
+
+pub struct FeatureHasher {
+    buckets: u64,
+    seed: u64,
+    /// Token → IDF, None weights tokens by their count
+    idf: Option<Idf>,
+    /// Scale vectors to unit (L2) length
+    normalize: bool,
+    /// JSONL field receiving the vectors
+    pub field: Option<String>,
+    /// Write the vectors to `<output_file>.vectors.bin`
+    pub binary: bool,
+}
+
+/// Smoothed IDF, ln((1 + N) / (1 + df)) + 1, as in scikit-learn
+struct Idf {
+    weights: HashMap<String, f32>,
+    /// IDF of tokens missing from the vocabulary file (df = 0)
+    unseen: f32,
+}
+
+impl FeatureHasher {
+    /// None when `buckets` is 0
+    pub fn new(buckets: usize, seed: u64, weights: &str, idf_file: Option<&str>, normalize: bool, field: Option<String>, binary: bool) -> Result<Option<Self>> {
+        if buckets == 0 {
+            return Ok(None);
+        }
+        if buckets > u32::MAX as usize {
+            return Err(invalid_input(format!("At most {} hashing features are supported", u32::MAX)));
+        }
+        if field.is_none() && !binary {
+            return Err(invalid_input("Hashing features require hashing_field or hashing_file".to_string()));
+        }
+        let idf = match (weights, idf_file) {
+            ("count", _) => None,
+            ("tfidf", Some(path)) => Some(Idf::load(Path::new(path))?),
+            ("tfidf", None) => return Err(invalid_input("TF-IDF weights require idf_file".to_string())),
+            _ => return Err(invalid_input(format!("Unknown hashing weights: {}", weights))),
+        };
+        Ok(Some(FeatureHasher { buckets: buckets as u64, seed, idf, normalize, field, binary }))
+    }
+
+    /// Vector of a normalized text (tokens separated by single spaces)
+    pub fn vectorize(&self, text: &str) -> SparseVector {
+        if text.is_empty() {
+            return Vec::new();
+        }
+
+        let mut vector: SparseVector = text
+            .split(' ')
+            .map(|token| {
+                let bucket = (xxh3_64_with_seed(token.as_bytes(), self.seed) % self.buckets) as u32;
+                let weight = self.idf.as_ref().map_or(1.0, |idf| idf.weight(token));
+                (bucket, weight)
+            })
+            .collect();
+
+        // Sum the weights of tokens sharing a bucket
+        vector.sort_unstable_by_key(|&(bucket, _)| bucket);
+        vector.dedup_by(|(bucket, weight), (kept_bucket, kept_weight)| {
+            let same = bucket == kept_bucket;
+            if same {
+                *kept_weight += *weight;
+            }
+            same
+        });
+
+        if self.normalize {
+            let norm = vector.iter().map(|(_, weight)| weight * weight).sum::<f32>().sqrt();
+            if norm > 0.0 {
+                vector.iter_mut().for_each(|(_, weight)| *weight /= norm);
+            }
+        }
+        vector
+    }
+}
+
+impl Idf {
+    fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let invalid = |n: usize, expected: &str| io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}:{}: expected {}", path.display(), n + 1, expected),
+        );
+
+        let rows: Vec<(&str, u64)> = contents
+            .lines()
+            .enumerate()
+            .map(|(n, line)| {
+                let mut columns = line.split('\t');
+                match (columns.next(), columns.next(), columns.next().and_then(|df| df.parse().ok())) {
+                    (Some(token), Some(_), Some(df)) => Ok((token, df)),
+                    _ => Err(invalid(n, "token<TAB>tf<TAB>df")),
+                }
+            })
+            .collect::<Result<_>>()?;
+
+        // Vocabularies written before the sidecar existed have no totals,
+        // the largest document frequency is the closest known bound
+        let totals = totals_path(path);
+        let documents = match fs::read_to_string(&totals) {
+            Ok(contents) => contents
+                .lines()
+                .find_map(|line| line.strip_prefix("documents\t"))
+                .and_then(|documents| documents.parse().ok())
+                .ok_or_else(|| io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: expected a documents<TAB>n line", totals.display()),
+                ))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => rows.iter().map(|&(_, df)| df).max().unwrap_or(0),
+            Err(e) => return Err(e),
+        };
+
+        let idf = |df: u64| ((1.0 + documents as f64) / (1.0 + df as f64)).ln() as f32 + 1.0;
+        let weights = rows.into_iter().map(|(token, df)| (token.to_string(), idf(df))).collect();
+        Ok(Idf { weights, unseen: idf(0) })
+    }
+
+    fn weight(&self, token: &str) -> f32 {
+        self.weights.get(token).copied().unwrap_or(self.unseen)
+    }
+}
+
+/// Appends a vector to the binary output: the number of entries (u32), then
+/// each bucket (u32) and weight (f32), all little-endian
+pub fn write_vector(writer: &mut impl Write, vector: &SparseVector) -> Result<()> {
+    writer.write_all(&(vector.len() as u32).to_le_bytes())?;
+    for (bucket, weight) in vector {
+        writer.write_all(&bucket.to_le_bytes())?;
+        writer.write_all(&weight.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vocab::Vocabulary;
+
+    #[test]
+    fn loads_idf_from_vocabulary() {
+        let mut vocabulary = Vocabulary::default();
+        for text in ["red fish", "blue fish", "red red", ""] {
+            vocabulary.add_document(text);
+        }
+        let path = std::env::temp_dir().join(format!("sstn-idf-{}.tsv", std::process::id()));
+        vocabulary.write(&path, 1).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().collect::<Vec<_>>(), ["red\t3\t2", "fish\t2\t2", "blue\t1\t1"]);
+        assert_eq!(fs::read_to_string(totals_path(&path)).unwrap(), "tokens\t6\ndocuments\t4\n");
+
+        let idf = Idf::load(&path).unwrap();
+        let expected = |df: f64| ((1.0 + 4.0) / (1.0 + df)).ln() as f32 + 1.0;
+        assert_eq!(idf.weight("red"), expected(2.0));
+        assert_eq!(idf.weight("blue"), expected(1.0));
+        assert_eq!(idf.weight("green"), expected(0.0));
+
+        // Without the sidecar, the largest document frequency stands in for the number of documents
+        fs::remove_file(totals_path(&path)).unwrap();
+        let idf = Idf::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let expected = |df: f64| ((1.0 + 2.0) / (1.0 + df)).ln() as f32 + 1.0;
+        assert_eq!(idf.weight("red"), expected(2.0));
+        assert_eq!(idf.weight("green"), expected(0.0));
+    }
+}
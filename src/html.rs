@@ -0,0 +1,138 @@
+//! html.rs
+//!
+//! Provides markup stripping for web-scraped text.
+//! Tags and comments become spaces, `<script>`/`<style>` blocks are dropped
+//! with their content, and HTML entities are decoded afterwards so that
+//! escaped text (`&lt;div&gt;`) is kept as text.
+//! This is a forgiving scanner, not a parser: malformed markup is left as-is.
+
+use std::borrow::Cow;
+use html_escape::decode_html_entities;
+
+/// Elements whose content is never text
+const RAW_TEXT_ELEMENTS: [&[u8]; 2] = [b"script", b"style"];
+
+/// Strips markup and decodes entities, borrowing the input when there is neither
+pub fn strip_html(text: &str) -> Cow<'_, str> {
+    let bytes = text.as_bytes();
+    if !bytes.contains(&b'<') {
+        return decode_html_entities(text);
+    }
+
+    let mut stripped = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut i = 0;
+    while let Some(offset) = bytes[i..].iter().position(|&b| b == b'<') {
+        let start = i + offset;
+        let Some(end) = markup_end(bytes, start) else {
+            // A lone '<' (e.g. "a < b") is text
+            i = start + 1;
+            continue;
+        };
+
+        stripped.push_str(&text[last..start]);
+        stripped.push(' ');
+        last = end;
+        i = end;
+    }
+    stripped.push_str(&text[last..]);
+
+    match decode_html_entities(&stripped) {
+        Cow::Borrowed(_) => Cow::Owned(stripped),
+        Cow::Owned(decoded) => Cow::Owned(decoded),
+    }
+}
+
+/// Returns the position right after the markup starting at `start`,
+/// or None when the '<' does not open a tag, comment or declaration,
+/// or when nothing closes it
+fn markup_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let rest = &bytes[start + 1..];
+    match rest.first()? {
+        // Comments run until "-->", which may contain '>'
+        b'!' if rest.starts_with(b"!--") => find(bytes, start + 4, b"-->").map(|i| i + 3),
+        // Doctype, CDATA, processing instructions and closing tags
+        b'!' | b'?' | b'/' => tag_end(bytes, start + 1),
+        c if c.is_ascii_alphabetic() => {
+            let end = tag_end(bytes, start + 1)?;
+            let name_len = rest.iter().take_while(|b| b.is_ascii_alphanumeric()).count();
+            let name = &rest[..name_len];
+            match RAW_TEXT_ELEMENTS.iter().find(|e| e.eq_ignore_ascii_case(name)) {
+                // Skip the content up to and including the closing tag
+                Some(element) => Some(closing_tag_end(bytes, end, element)),
+                None => Some(end),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Position right after the '>' closing a tag, ignoring '>' inside quoted attributes,
+/// or None when another tag opens first or the text ends
+fn tag_end(bytes: &[u8], from: usize) -> Option<usize> {
+    let mut quote: Option<u8> = None;
+    for (i, &b) in bytes.iter().enumerate().skip(from) {
+        match (quote, b) {
+            (Some(q), _) if b == q => quote = None,
+            // A quote running into the next tag was never closed
+            (Some(_), b'<') => break,
+            (None, b'"' | b'\'') => quote = Some(b),
+            (None, b'>') => return Some(i + 1),
+            // The '<' we started from was text (x<y and <b>more</b>)
+            (None, b'<') => return None,
+            _ => {}
+        }
+    }
+
+    // An unterminated quote ends at the next '>'
+    let end = from + bytes[from..].iter().position(|&b| b == b'>' || b == b'<')?;
+    (bytes[end] == b'>').then_some(end + 1)
+}
+
+/// Position right after `</element>`, or the end of the text if it is never closed
+fn closing_tag_end(bytes: &[u8], from: usize, element: &[u8]) -> usize {
+    let mut i = from;
+    while let Some(pos) = find(bytes, i, b"</") {
+        let name = &bytes[pos + 2..];
+        if name.len() >= element.len() && name[..element.len()].eq_ignore_ascii_case(element) {
+            return tag_end(bytes, pos + 2).unwrap_or(bytes.len());
+        }
+        i = pos + 2;
+    }
+    bytes.len()
+}
+
+fn find(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes.get(from..)?
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|i| from + i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_tags_and_decodes_entities() {
+        assert_eq!(strip_html("<p class=\"a>b\">Hello</p> &lt;div&gt;"), " Hello  <div>");
+        assert_eq!(strip_html("a<!-- <b> -->b<script>x > y</script>c"), "a b c");
+    }
+
+    #[test]
+    fn keeps_unclosed_markup_as_text() {
+        assert_eq!(strip_html("x<y and more text here"), "x<y and more text here");
+        assert_eq!(
+            strip_html("I <3 you and x<y but rest here is gone"),
+            "I <3 you and x<y but rest here is gone",
+        );
+        assert_eq!(strip_html("x<y and <b>more</b>"), "x<y and  more ");
+        assert_eq!(strip_html("a <!-- never closed"), "a <!-- never closed");
+    }
+
+    #[test]
+    fn unterminated_quote_ends_at_next_bracket() {
+        assert_eq!(strip_html("<img alt='Bob>Bob's car is red"), " Bob's car is red");
+        assert_eq!(strip_html("<a href=\"x>link</a> and \"quoted\" text"), " link  and \"quoted\" text");
+    }
+}
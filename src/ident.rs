@@ -0,0 +1,127 @@
+//! ident.rs
+//!
+//! Provides identifier splitting for source code and API docs
+//! (camelCase, PascalCase, acronyms, letter/digit runs, snake_case and kebab-case).
+//! Each rule can be toggled on its own.
+
+/// Splits a word into its identifier parts
+pub struct IdentifierSplitter {
+    /// camelCase → camel Case
+    pub camel_case: bool,
+    /// HTTPServer → HTTP Server
+    pub acronyms: bool,
+    /// iPhone12 → iPhone 12
+    pub digits: bool,
+    /// get_user_id → get user id (kebab-case too)
+    pub snake_case: bool,
+}
+
+impl IdentifierSplitter {
+    /// Calls `f` with each non-empty part of the word, in order
+    pub fn split<'a>(&self, word: &'a str, mut f: impl FnMut(&'a str)) {
+        let mut emit = |part: &'a str| if !part.is_empty() { f(part) };
+
+        let mut start = 0;
+        let mut prev: Option<char> = None;
+        let mut chars = word.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if self.snake_case && (c == '_' || c == '-') {
+                emit(&word[start..i]);
+                start = i + c.len_utf8();
+                prev = None;
+                continue;
+            }
+
+            if let Some(p) = prev {
+                let next = chars.peek().map(|&(_, n)| n);
+                let boundary =
+                    (self.camel_case && p.is_lowercase() && c.is_uppercase())
+                    // The last capital of an acronym starts the next word: XMLHttp → XML Http
+                    || (self.acronyms && p.is_uppercase() && c.is_uppercase() && next.is_some_and(char::is_lowercase))
+                    || (self.digits && p.is_alphanumeric() && c.is_alphanumeric() && p.is_numeric() != c.is_numeric());
+                if boundary {
+                    emit(&word[start..i]);
+                    start = i;
+                }
+            }
+            prev = Some(c);
+        }
+        emit(&word[start..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORDS: [&str; 4] = ["HTTPServer", "XMLHttpRequest", "iPhone12", "get_user_id"];
+
+    fn split(splitter: &IdentifierSplitter) -> Vec<Vec<&'static str>> {
+        WORDS
+            .iter()
+            .map(|word| {
+                let mut parts = Vec::new();
+                splitter.split(word, |part| parts.push(part));
+                parts
+            })
+            .collect()
+    }
+
+    fn splitter(camel_case: bool, acronyms: bool, digits: bool, snake_case: bool) -> IdentifierSplitter {
+        IdentifierSplitter { camel_case, acronyms, digits, snake_case }
+    }
+
+    #[test]
+    fn all_rules_off_keeps_words() {
+        let expected: Vec<Vec<&str>> = WORDS.iter().map(|&word| vec![word]).collect();
+        assert_eq!(split(&splitter(false, false, false, false)), expected);
+    }
+
+    #[test]
+    fn each_rule_on_its_own() {
+        assert_eq!(
+            split(&splitter(true, false, false, false)),
+            [vec!["HTTPServer"], vec!["XMLHttp", "Request"], vec!["i", "Phone12"], vec!["get_user_id"]]
+        );
+        assert_eq!(
+            split(&splitter(false, true, false, false)),
+            [vec!["HTTP", "Server"], vec!["XML", "HttpRequest"], vec!["iPhone12"], vec!["get_user_id"]]
+        );
+        assert_eq!(
+            split(&splitter(false, false, true, false)),
+            [vec!["HTTPServer"], vec!["XMLHttpRequest"], vec!["iPhone", "12"], vec!["get_user_id"]]
+        );
+        assert_eq!(
+            split(&splitter(false, false, false, true)),
+            [vec!["HTTPServer"], vec!["XMLHttpRequest"], vec!["iPhone12"], vec!["get", "user", "id"]]
+        );
+    }
+
+    #[test]
+    fn all_rules_on() {
+        assert_eq!(
+            split(&splitter(true, true, true, true)),
+            [vec!["HTTP", "Server"], vec!["XML", "Http", "Request"], vec!["i", "Phone", "12"], vec!["get", "user", "id"]]
+        );
+    }
+
+    #[test]
+    fn each_rule_off_on_its_own() {
+        assert_eq!(
+            split(&splitter(false, true, true, true)),
+            [vec!["HTTP", "Server"], vec!["XML", "HttpRequest"], vec!["iPhone", "12"], vec!["get", "user", "id"]]
+        );
+        assert_eq!(
+            split(&splitter(true, false, true, true)),
+            [vec!["HTTPServer"], vec!["XMLHttp", "Request"], vec!["i", "Phone", "12"], vec!["get", "user", "id"]]
+        );
+        assert_eq!(
+            split(&splitter(true, true, false, true)),
+            [vec!["HTTP", "Server"], vec!["XML", "Http", "Request"], vec!["i", "Phone12"], vec!["get", "user", "id"]]
+        );
+        assert_eq!(
+            split(&splitter(true, true, true, false)),
+            [vec!["HTTP", "Server"], vec!["XML", "Http", "Request"], vec!["i", "Phone", "12"], vec!["get_user_id"]]
+        );
+    }
+}
@@ -72,9 +72,9 @@ impl Iterator for ArchiveReader {
                     self.buffer.pop();
                 }
 
-                let mut as_bytes = self.buffer.as_mut();
+                let as_bytes = self.buffer.as_mut();
 
-                let val : OwnedValue = match simd_json::to_owned_value(&mut as_bytes) {
+                let val : OwnedValue = match simd_json::to_owned_value(as_bytes) {
                     Ok(val) => val,
                     Err(e) => return Some(Err(io::Error::new(io::ErrorKind::InvalidData, e))),
                 };
@@ -0,0 +1,60 @@
+//! langid.rs
+//!
+//! Provides per-document language identification.
+//! Backed by whatlang's trigram model, which is embedded in the binary.
+
+use rust_stemmers::Algorithm;
+use whatlang::Lang;
+
+// Trigram statistics settle long before this, and detection cost grows with the input
+const MAX_DETECTION_BYTES: usize = 4096;
+
+pub struct Detection {
+    /// ISO 639-3 code (e.g. "eng", "rus", "cmn")
+    pub code: &'static str,
+    pub confidence: f64,
+    pub reliable: bool,
+    /// Stemmer for the language, if Snowball has one
+    pub algorithm: Option<Algorithm>,
+}
+
+/// Detects the language of a text, looking at its first few kilobytes only
+pub fn detect(text: &str) -> Option<Detection> {
+    let mut end = text.len().min(MAX_DETECTION_BYTES);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    let info = whatlang::detect(&text[..end])?;
+    Some(Detection {
+        code: info.lang().code(),
+        confidence: info.confidence(),
+        reliable: info.is_reliable(),
+        algorithm: stemmer_algorithm(info.lang()),
+    })
+}
+
+fn stemmer_algorithm(lang: Lang) -> Option<Algorithm> {
+    let algorithm = match lang {
+        Lang::Ara => Algorithm::Arabic,
+        Lang::Dan => Algorithm::Danish,
+        Lang::Nld => Algorithm::Dutch,
+        Lang::Eng => Algorithm::English,
+        Lang::Fin => Algorithm::Finnish,
+        Lang::Fra => Algorithm::French,
+        Lang::Deu => Algorithm::German,
+        Lang::Ell => Algorithm::Greek,
+        Lang::Hun => Algorithm::Hungarian,
+        Lang::Ita => Algorithm::Italian,
+        Lang::Nob => Algorithm::Norwegian,
+        Lang::Por => Algorithm::Portuguese,
+        Lang::Ron => Algorithm::Romanian,
+        Lang::Rus => Algorithm::Russian,
+        Lang::Spa => Algorithm::Spanish,
+        Lang::Swe => Algorithm::Swedish,
+        Lang::Tam => Algorithm::Tamil,
+        Lang::Tur => Algorithm::Turkish,
+        _ => return None,
+    };
+    Some(algorithm)
+}
@@ -4,89 +4,438 @@
     Provides functions for interacting with the Python API.
 */
 
+pub mod config;
+pub mod counts;
+pub mod dedup;
+pub mod entity;
+pub mod filter;
+pub mod hashing;
+pub mod html;
+pub mod ident;
 pub mod io;
+pub mod langid;
+pub mod minhash;
+pub mod ngram;
 pub mod norm;
+pub mod phrase;
+pub mod pipeline;
+pub mod protect;
+pub mod quality;
+pub mod rewrite;
+pub mod segment;
 pub mod set;
+pub mod simd;
+pub mod simhash;
+pub mod stem;
+pub mod surface;
+pub mod synonym;
+pub mod token;
+pub mod unicode;
+pub mod vocab;
 
 use pyo3::{prelude::*, types::PyString};
 use rayon::ThreadPool;
-use crate::norm::{_normalize_text as _inner_normalize_text, normalize_owned_value};
+use crate::norm::{_normalize_text as _inner_normalize_text, insert, normalize_owned_value};
 use crate::io::{ArchiveWriter, ArchiveReader};
+use crate::config::NormalizerConfig;
+use crate::dedup::{DedupMethod, DedupMode, Deduplicator, Fingerprint, Location};
+use crate::hashing::SparseVector;
+use crate::minhash::Lsh;
+use crate::pipeline::Pipeline;
+use crate::surface::SurfaceForms;
+use crate::vocab::Vocabulary;
 use simd_json::OwnedValue;
+use simd_json::prelude::*;
 use rayon::ThreadPoolBuilder;
 use rayon::prelude::*;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// The config of the last compiled pipeline, along with the modification times of the files it reads
+type PipelineKey = (NormalizerConfig, [Option<SystemTime>; 3]);
+
+/// The last compiled pipeline, reused as long as the config and its files are unchanged
+/// (normalize_text is typically called in a loop with the same config)
+static PIPELINE_CACHE: Mutex<Option<(PipelineKey, Arc<Pipeline>)>> = Mutex::new(None);
+
+/// Compiles the config handed over by Python, or returns the cached pipeline
+/// Invalid options become a ValueError, unreadable files (phrase lists...) an IOError
+fn build_pipeline(config: &NormalizerConfig) -> PyResult<Arc<Pipeline>> {
+    // Only the files that are set are checked, most configs have none
+    let modified = [&config.synonyms_file, &config.phrases_file, &config.idf_file]
+        .map(|path| path.as_ref().and_then(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok()));
+
+    // The config is compared in place, it is only cloned when a new pipeline is compiled
+    let mut cache = PIPELINE_CACHE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(((cached_config, cached_modified), pipeline)) = cache.as_ref()
+        && cached_config == config
+        && *cached_modified == modified {
+        return Ok(Arc::clone(pipeline));
+    }
+
+    let pipeline = Arc::new(Pipeline::new(config).map_err(|e| match e.kind() {
+        std::io::ErrorKind::InvalidInput => PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid config: {}", e)),
+        _ => PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to load config files: {}", e)),
+    })?);
+    *cache = Some(((config.clone(), modified), Arc::clone(&pipeline)));
+    Ok(pipeline)
+}
 
 #[pyfunction]
 // Mangling the name so the python side can have neat docstrings
 fn __normalize_text(
     text: &Bound<'_, PyString>,
+    config: NormalizerConfig,
 ) -> PyResult<String> {
     // Convert the text to a string
     let text = text.to_string_lossy();
+    let pipeline = build_pipeline(&config)?;
 
-    _inner_normalize_text(&text)
+    _inner_normalize_text(&text, &pipeline)
         .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Normalization error: {}", e)))
-        .map(|s| s.to_string())
 }
 
 #[pyfunction]
-fn __normalize_jsonl_file(
-    input_file: &str,
-    output_file: &str,
+fn __normalize_jsonl_files(
+    input_files: Vec<String>,
+    output_files: Vec<String>,
     text_column: &str,
     workers: usize,
+    config: NormalizerConfig,
+    report_prefix: &str,
 ) -> PyResult<()> {
 
+    let pipeline = build_pipeline(&config)?;
+
     // Release the GIL for the duration of the heavy IO/CPU work
     Python::with_gil(|py| {
         py.allow_threads(|| {
-            let input_path = std::path::Path::new(input_file);
-            let output_path = std::path::Path::new(output_file);
-
-            let reader = ArchiveReader::new(
-                &input_path,
-            ).map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create ArchiveReader: {}", e)))?;
-
-            let processed : Vec<OwnedValue> = reader.filter_map(|x| x.ok()).collect();
-
             let pool : ThreadPool = ThreadPoolBuilder::new()
                 .num_threads(workers)
                 .build()
                 .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to create thread pool: {}", e)))?;
 
-            let normalized: Vec<OwnedValue> = pool.install(|| {
-                processed.into_par_iter().map(|mut val: OwnedValue| {
-                    // Normalize the text in the JSON object
-                    normalize_owned_value(&mut val, text_column)
-                        .expect("Failed to normalize text");
-                    // Print the normalized JSON object
-                    val
-                }).collect::<Vec<_>>()
-            });
-
-            let mut writer = ArchiveWriter::new(
-                &output_path,
-            ).map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create ArchiveWriter: {}", e)))?;
-
-            for val in normalized.into_iter() {
-                // Write the normalized JSON object to the output file
-                writer.write(&val)
+            // Shared by all files, so that repeats are found across them
+            let mut deduplicator = Deduplicator::new(pipeline.dedup_method);
+            let mut lsh = pipeline.minhash.as_ref().filter(|minhash| minhash.bands > 0).map(Lsh::new);
+            let mut vocabulary = Vocabulary::default();
+
+            for (input_file, output_file) in input_files.iter().zip(&output_files) {
+                let input_path = std::path::Path::new(input_file);
+                let output_path = std::path::Path::new(output_file);
+
+                let reader = ArchiveReader::new(
+                    input_path,
+                ).map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create ArchiveReader: {}", e)))?;
+
+                // Unreadable lines are skipped, locations use the line numbers in the file
+                let (lines, mut records) : (Vec<usize>, Vec<OwnedValue>) = reader
+                    .enumerate()
+                    .filter_map(|(line, x)| x.ok().map(|val| (line + 1, val)))
+                    .unzip();
+
+                // Whether each record makes it to the output (quality filters, then deduplication)
+                let mut kept = vec![true; records.len()];
+                // Each worker counts surface forms on its own, the counts are merged at the end
+                let surface: SurfaceForms = pool.install(|| {
+                    records.par_iter_mut().zip(kept.par_iter_mut()).fold(SurfaceForms::default, |mut surface, (val, kept)| {
+                        // Normalize the text in the JSON object
+                        *kept = normalize_owned_value(val, text_column, &pipeline, config.surface_forms.then_some(&mut surface))
+                            .expect("Failed to normalize text");
+                        surface
+                    }).reduce(SurfaceForms::default, SurfaceForms::merge)
+                });
+
+                // Records without text (or left empty by normalization) are never fingerprinted, nor deduplicated
+                let fingerprints: Vec<Option<Fingerprint>> = match pipeline.fingerprints_documents() {
+                    true => pool.install(|| records
+                        .par_iter()
+                        .zip(&kept)
+                        .map(|(val, &kept)| val.get_str(text_column)
+                            .filter(|text| kept && !text.is_empty())
+                            .map(|text| Fingerprint::new(text, &pipeline)))
+                        .collect()),
+                    false => records.iter().map(|_| None).collect(),
+                };
+                let file = deduplicator.add_file(input_file);
+                let locations: Vec<Location> = lines.into_iter().map(|line| Location { file, line }).collect();
+                deduplicate(&mut records, &locations, &mut kept, fingerprints, &mut deduplicator, lsh.as_mut(), &pipeline)
                     .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write JSON object: {}", e)))?;
+
+                // Counted on the records actually written
+                if config.vocabulary {
+                    let counts = pool.install(|| {
+                        records.par_iter().zip(&kept).filter(|(_, kept)| **kept).fold(Vocabulary::default, |mut counts, (val, _)| {
+                            if let Some(text) = val.get_str(text_column) {
+                                counts.add_document(text);
+                            }
+                            counts
+                        }).reduce(Vocabulary::default, Vocabulary::merge)
+                    });
+                    vocabulary = vocabulary.merge(counts);
+                }
+
+                // Vectorized from the final text of the records actually written
+                let vectors: Vec<Option<SparseVector>> = match &pipeline.hasher {
+                    Some(hasher) => pool.install(|| records
+                        .par_iter()
+                        .zip(&kept)
+                        .map(|(val, &kept)| kept.then(|| hasher.vectorize(val.get_str(text_column).unwrap_or(""))))
+                        .collect()),
+                    None => records.iter().map(|_| None).collect(),
+                };
+                let mut vector_writer = match pipeline.hasher.as_ref().filter(|hasher| hasher.binary) {
+                    Some(_) => Some(std::io::BufWriter::new(
+                        std::fs::File::create(format!("{}.vectors.bin", output_file))
+                            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create vector file: {}", e)))?,
+                    )),
+                    None => None,
+                };
+
+                let mut writer = ArchiveWriter::new(
+                    output_path,
+                ).map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create ArchiveWriter: {}", e)))?;
+
+                for ((mut val, kept), vector) in records.into_iter().zip(kept).zip(vectors) {
+                    if !kept {
+                        continue;
+                    }
+                    if let Some(vector) = vector {
+                        // One vector per written record, empty when there is no text, so that rows line up
+                        if let Some(vector_writer) = vector_writer.as_mut() {
+                            hashing::write_vector(vector_writer, &vector)
+                                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write vector: {}", e)))?;
+                        }
+                        if let Some(field) = pipeline.hasher.as_ref().and_then(|hasher| hasher.field.as_ref()) {
+                            let pairs: Vec<OwnedValue> = vector
+                                .into_iter()
+                                .map(|(bucket, weight)| OwnedValue::from(vec![OwnedValue::from(bucket), OwnedValue::from(weight as f64)]))
+                                .collect();
+                            insert(&mut val, field, pairs)
+                                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write JSON object: {}", e)))?;
+                        }
+                    }
+
+                    // Write the normalized JSON object to the output file
+                    writer.write(&val)
+                        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write JSON object: {}", e)))?;
+                }
+                writer.close()
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to close ArchiveWriter: {}", e)))?;
+                if let Some(mut vector_writer) = vector_writer {
+                    vector_writer.flush()
+                        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write vector: {}", e)))?;
+                }
+
+                if config.surface_forms {
+                    let sidecar = format!("{}.surface.tsv", output_file);
+                    surface.write(std::path::Path::new(&sidecar))
+                        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write surface forms: {}", e)))?;
+                }
+            }
+
+            if pipeline.dedup != DedupMode::Off {
+                let report = format!("{}duplicates.tsv", report_prefix);
+                deduplicator.write_report(std::path::Path::new(&report))
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write dedup report: {}", e)))?;
+            }
+            if config.vocabulary {
+                let report = format!("{}vocab.tsv", report_prefix);
+                vocabulary.write(std::path::Path::new(&report), config.vocabulary_min_count)
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write vocabulary: {}", e)))?;
             }
-            writer.close()
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to close ArchiveWriter: {}", e)))?;
             Ok::<(), PyErr>(())
         })
     })?;
     Ok::<(), PyErr>(())
 }
 
+/// Checks the records of a file for repeats, in input order, and writes their fingerprint fields
+/// Repeats are flagged, or dropped by turning their `kept` entry false
+fn deduplicate(
+    records: &mut [OwnedValue],
+    locations: &[Location],
+    kept: &mut [bool],
+    fingerprints: Vec<Option<Fingerprint>>,
+    deduplicator: &mut Deduplicator,
+    mut lsh: Option<&mut Lsh>,
+    pipeline: &Pipeline,
+) -> std::io::Result<()> {
+    for (record, (val, fingerprint)) in records.iter_mut().zip(fingerprints).enumerate() {
+        // Dropped records are not first occurrences of anything
+        if !kept[record] {
+            continue;
+        }
+        // Records without text are never duplicates, but get the same (null) fields as the others
+        let Some(fingerprint) = fingerprint else {
+            annotate(val, pipeline, None, None, None)?;
+            continue;
+        };
+        let cluster = lsh.as_deref_mut()
+            .zip(fingerprint.signature.as_deref())
+            .map(|(lsh, signature)| lsh.assign(signature));
+        let key = match pipeline.dedup_method {
+            DedupMethod::Exact => Some(fingerprint.hash),
+            DedupMethod::MinHash => cluster.map(u128::from),
+        };
+
+        // Repeats are checked in input order, so the first occurrence is the one kept
+        let first = match pipeline.dedup {
+            DedupMode::Off => None,
+            _ => key.and_then(|key| deduplicator.check(key, locations[record])),
+        };
+        if pipeline.dedup == DedupMode::Drop && first.is_some() {
+            kept[record] = false;
+            continue;
+        }
+        annotate(val, pipeline, Some(fingerprint), cluster, first.map(|first| deduplicator.describe(first)))?;
+    }
+    Ok(())
+}
+
+/// Writes the hash, MinHash, SimHash and duplicate fields of a record
+/// Records without a fingerprint (no text) get null in each of them
+fn annotate(val: &mut OwnedValue, pipeline: &Pipeline, fingerprint: Option<Fingerprint>, cluster: Option<u64>, first: Option<String>) -> std::io::Result<()> {
+    let (hash, signature, simhash) = match fingerprint {
+        Some(fingerprint) => (Some(fingerprint.hash), fingerprint.signature, fingerprint.simhash),
+        None => (None, None, None),
+    };
+    if let Some(field) = &pipeline.hash_field {
+        insert(val, field, hash.map(|hash| format!("{:032x}", hash)))?;
+    }
+    if let Some(field) = &pipeline.minhash_field {
+        insert(val, field, signature)?;
+    }
+    if let Some(field) = &pipeline.cluster_field {
+        insert(val, field, cluster)?;
+    }
+    if let Some(field) = &pipeline.simhash_field {
+        insert(val, field, simhash.map(|simhash| format!("{:016x}", simhash)))?;
+    }
+    if pipeline.dedup == DedupMode::Flag {
+        // Null for first occurrences
+        insert(val, &pipeline.duplicate_field, first)?;
+    }
+    Ok(())
+}
+
+
+#[pyfunction]
+fn __find_simhash_pairs(
+    input_files: Vec<String>,
+    output_file: &str,
+    field: &str,
+    max_distance: u32,
+) -> PyResult<usize> {
+    if max_distance >= 64 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("max_distance must be below 64"));
+    }
+
+    Python::with_gil(|py| {
+        py.allow_threads(|| {
+            // Only the fingerprints and their locations are kept
+            let mut simhashes: Vec<u64> = Vec::new();
+            let mut locations: Vec<Location> = Vec::new();
+            for (file, input_file) in input_files.iter().enumerate() {
+                let reader = ArchiveReader::new(
+                    std::path::Path::new(input_file),
+                ).map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create ArchiveReader: {}", e)))?;
+
+                for (line, val) in reader.enumerate().filter_map(|(line, x)| x.ok().map(|val| (line + 1, val))) {
+                    // Records without a valid fingerprint are skipped
+                    if let Some(simhash) = val.get_str(field).and_then(|hex| u64::from_str_radix(hex, 16).ok()) {
+                        simhashes.push(simhash);
+                        locations.push(Location { file, line });
+                    }
+                }
+            }
+
+            let pairs = simhash::find_pairs(&simhashes, max_distance);
+
+            let mut writer = std::io::BufWriter::new(
+                std::fs::File::create(output_file)
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create {}: {}", output_file, e)))?,
+            );
+            for &(i, j, distance) in &pairs {
+                writeln!(writer, "{}\t{}\t{}", locations[i].describe(&input_files), locations[j].describe(&input_files), distance)
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write pairs: {}", e)))?;
+            }
+            writer.flush()
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write pairs: {}", e)))?;
+            Ok::<usize, PyErr>(pairs.len())
+        })
+    })
+}
+
 
 #[pymodule]
 #[pyo3(name = "_norm")]
 fn register_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
     // Add the normalize_text function to the module
     m.add_function(wrap_pyfunction!(__normalize_text, m)?)?;
-    m.add_function(wrap_pyfunction!(__normalize_jsonl_file, m)?)?;
+    m.add_function(wrap_pyfunction!(__normalize_jsonl_files, m)?)?;
+    m.add_function(wrap_pyfunction!(__find_simhash_pairs, m)?)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deduplicates records read from `file.jsonl`, whose lines 3 and 6 are blank
+    fn deduplicate_records(dedup: &str, lines: &[&str]) -> (Vec<OwnedValue>, Vec<bool>) {
+        let config = NormalizerConfig { dedup: dedup.to_string(), hash_field: Some("hash".to_string()), ..Default::default() };
+        let pipeline = Pipeline::new(&config).unwrap();
+
+        let mut records: Vec<OwnedValue> = lines
+            .iter()
+            .map(|line| simd_json::to_owned_value(&mut line.as_bytes().to_vec()).unwrap())
+            .collect();
+        let fingerprints = records
+            .iter()
+            .map(|val| val.get_str("text").filter(|text| !text.is_empty()).map(|text| Fingerprint::new(text, &pipeline)))
+            .collect();
+        let mut deduplicator = Deduplicator::new(pipeline.dedup_method);
+        let file = deduplicator.add_file("file.jsonl");
+        let locations: Vec<Location> = [1, 2, 4, 5, 7].iter().map(|&line| Location { file, line }).collect();
+        let mut kept = vec![true; records.len()];
+
+        deduplicate(&mut records, &locations, &mut kept, fingerprints, &mut deduplicator, None, &pipeline).unwrap();
+        (records, kept)
+    }
+
+    const LINES: [&str; 5] = [
+        r#"{"text": "red fish"}"#,
+        r#"{"text": "blue fish"}"#,
+        r#"{"text": "red fish"}"#,
+        r#"{"id": 4}"#,
+        r#"{"text": "blue fish"}"#,
+    ];
+
+    #[test]
+    fn drop_leaves_out_repeats() {
+        let (records, kept) = deduplicate_records("drop", &LINES);
+        assert_eq!(kept, [true, true, false, true, false]);
+        assert!(records.iter().all(|val| val.get("duplicate_of").is_none()));
+    }
+
+    #[test]
+    fn flag_points_at_the_line_of_the_first_occurrence() {
+        let (records, kept) = deduplicate_records("flag", &LINES);
+        assert!(kept.iter().all(|&kept| kept));
+        let flags: Vec<Option<&str>> = records.iter().map(|val| val.get_str("duplicate_of")).collect();
+        assert_eq!(flags, [None, None, Some("file.jsonl:1"), None, Some("file.jsonl:2")]);
+        // First occurrences get null rather than no field
+        assert!(records.iter().all(|val| val.get("duplicate_of").is_some_and(|field| field.is_null() || field.is_str())));
+    }
+
+    #[test]
+    fn records_without_text_get_null_fields() {
+        let (records, kept) = deduplicate_records("flag", &LINES);
+        assert!(kept[3]);
+        assert!(records[3].get("hash").is_some_and(|hash| hash.is_null()));
+        assert!(records[3].get("duplicate_of").is_some_and(|first| first.is_null()));
+        assert_eq!(records[0].get_str("hash").map(str::len), Some(32));
+    }
+}
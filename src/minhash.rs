@@ -0,0 +1,193 @@
+//! minhash.rs
+//!
+//! Provides MinHash signatures over word shingles of the normalized text, and
+//! LSH banding to group near-duplicate documents into clusters.
+//! Clusters are assigned in input order: a document joins the cluster of the
+//! earliest document it shares a band with, or starts a new one.
+
+use std::collections::HashMap;
+use std::io::Result;
+use xxhash_rust::xxh3::{xxh3_64, Xxh3};
+use crate::pipeline::invalid_input;
+
+/// Permutations are drawn modulo this Mersenne prime (2^61 - 1)
+const PRIME: u64 = (1 << 61) - 1;
+
+pub struct MinHasher {
+    /// Words per shingle
+    shingle_size: usize,
+    /// (a, b) of each permutation h(x) = (a·x + b) mod PRIME
+    permutations: Vec<(u64, u64)>,
+    /// Number of LSH bands, 0 when clustering is off
+    pub bands: usize,
+}
+
+impl MinHasher {
+    /// None when `permutations` is 0
+    pub fn new(permutations: usize, shingle_size: usize, seed: u64, bands: usize) -> Result<Option<Self>> {
+        if permutations == 0 {
+            if bands > 0 {
+                return Err(invalid_input("LSH requires minhash_permutations".to_string()));
+            }
+            return Ok(None);
+        }
+        if shingle_size == 0 {
+            return Err(invalid_input("The shingle size must be at least 1".to_string()));
+        }
+        if bands > 0 && !permutations.is_multiple_of(bands) {
+            return Err(invalid_input(format!("{} permutations cannot be split into {} LSH bands", permutations, bands)));
+        }
+
+        let mut state = seed;
+        let permutations = (0..permutations)
+            .map(|_| (1 + splitmix64(&mut state) % (PRIME - 1), splitmix64(&mut state) % PRIME))
+            .collect();
+        Ok(Some(MinHasher { shingle_size, permutations, bands }))
+    }
+
+    /// Signature of a normalized text (words separated by single spaces)
+    /// Texts shorter than a shingle are a single shingle
+    pub fn signature(&self, text: &str) -> Vec<u64> {
+        let mut signature = vec![u64::MAX; self.permutations.len()];
+
+        let words: Vec<(usize, usize)> = text
+            .split(' ')
+            .map(|word| {
+                let start = word.as_ptr() as usize - text.as_ptr() as usize;
+                (start, start + word.len())
+            })
+            .collect();
+        let shingles = words.len().saturating_sub(self.shingle_size) + 1;
+        for i in 0..shingles {
+            let last = (i + self.shingle_size).min(words.len()) - 1;
+            let shingle = xxh3_64(&text.as_bytes()[words[i].0..words[last].1]) & PRIME;
+            for (min, &permutation) in signature.iter_mut().zip(&self.permutations) {
+                *min = (*min).min(permute(shingle, permutation));
+            }
+        }
+        signature
+    }
+
+    /// Rows per LSH band
+    fn rows(&self) -> usize {
+        self.permutations.len() / self.bands
+    }
+}
+
+/// (a·x + b) mod PRIME, folding the product instead of dividing
+fn permute(x: u64, (a, b): (u64, u64)) -> u64 {
+    let y = a as u128 * x as u128 + b as u128;
+    let y = (y & PRIME as u128) + (y >> 61);
+    let y = ((y & PRIME as u128) + (y >> 61)) as u64;
+    if y >= PRIME { y - PRIME } else { y }
+}
+
+/// Seeded generator of the permutation coefficients
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// LSH index shared by all the files of a run
+/// Only one hash per band and document is kept, not the signatures
+pub struct Lsh {
+    rows: usize,
+    /// Band hash → cluster, one table per band
+    buckets: Vec<HashMap<u64, u64>>,
+    clusters: u64,
+}
+
+impl Lsh {
+    pub fn new(minhash: &MinHasher) -> Self {
+        Lsh {
+            rows: minhash.rows(),
+            buckets: (0..minhash.bands).map(|_| HashMap::new()).collect(),
+            clusters: 0,
+        }
+    }
+
+    /// Cluster of a document, numbered from 0 in order of first appearance
+    /// When bands match several clusters, the oldest one wins
+    pub fn assign(&mut self, signature: &[u64]) -> u64 {
+        let keys: Vec<u64> = signature
+            .chunks(self.rows)
+            .map(|band| {
+                let mut hasher = Xxh3::new();
+                for value in band {
+                    hasher.update(&value.to_le_bytes());
+                }
+                hasher.digest()
+            })
+            .collect();
+
+        let cluster = keys
+            .iter()
+            .zip(&self.buckets)
+            .filter_map(|(key, buckets)| buckets.get(key))
+            .min()
+            .copied()
+            .unwrap_or_else(|| {
+                self.clusters += 1;
+                self.clusters - 1
+            });
+        for (key, buckets) in keys.into_iter().zip(&mut self.buckets) {
+            buckets.entry(key).or_insert(cluster);
+        }
+        cluster
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(range: std::ops::Range<usize>) -> String {
+        range.map(|n| format!("w{}", n)).collect::<Vec<_>>().join(" ")
+    }
+
+    #[test]
+    fn same_shingles_same_signature() {
+        let minhash = MinHasher::new(64, 1, 7, 0).unwrap().unwrap();
+        assert_eq!(minhash.signature("red fish blue fish"), minhash.signature("fish blue red"));
+        assert_ne!(minhash.signature("red fish blue fish"), minhash.signature("red fish"));
+
+        let minhash = MinHasher::new(64, 2, 7, 0).unwrap().unwrap();
+        assert_eq!(minhash.signature("a b c a b"), minhash.signature("c a b c a b"));
+    }
+
+    #[test]
+    fn estimates_jaccard_similarity() {
+        // 50 shared words out of 150: J = 1/3
+        let minhash = MinHasher::new(512, 1, 7, 0).unwrap().unwrap();
+        let a = minhash.signature(&words(0..100));
+        let b = minhash.signature(&words(50..150));
+        let estimate = a.iter().zip(&b).filter(|(a, b)| a == b).count() as f64 / a.len() as f64;
+        assert!((estimate - 1.0 / 3.0).abs() < 0.08, "estimate {}", estimate);
+    }
+
+    #[test]
+    fn rejects_bands_not_dividing_permutations() {
+        assert!(MinHasher::new(128, 5, 7, 3).is_err());
+        assert!(MinHasher::new(0, 5, 7, 4).is_err());
+        assert!(MinHasher::new(128, 0, 7, 0).is_err());
+        assert!(MinHasher::new(128, 5, 7, 32).unwrap().is_some());
+        assert!(MinHasher::new(0, 5, 7, 0).unwrap().is_none());
+    }
+
+    #[test]
+    fn clusters_near_duplicates() {
+        let minhash = MinHasher::new(128, 3, 7, 32).unwrap().unwrap();
+        let mut lsh = Lsh::new(&minhash);
+
+        let original = words(0..200);
+        let edited = original.replacen("w100", "changed", 1);
+        assert_eq!(lsh.assign(&minhash.signature(&original)), 0);
+        assert_eq!(lsh.assign(&minhash.signature(&words(1000..1200))), 1);
+        assert_eq!(lsh.assign(&minhash.signature(&edited)), 0);
+        assert_eq!(lsh.assign(&minhash.signature(&words(2000..2200))), 2);
+        assert_eq!(lsh.assign(&minhash.signature(&words(1000..1199))), 1);
+    }
+}
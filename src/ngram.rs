@@ -0,0 +1,48 @@
+//! ngram.rs
+//!
+//! Provides word n-grams over the final token stream (after stemming and
+//! phrase merging), for feature extraction and phrase mining.
+
+use std::io::Result;
+use crate::pipeline::invalid_input;
+use crate::token::Token;
+
+pub struct NgramGenerator {
+    min: usize,
+    max: usize,
+    /// Joins the tokens of an n-gram
+    separator: String,
+    /// Array field receiving the n-grams, None replaces the text with them
+    pub field: Option<String>,
+}
+
+impl NgramGenerator {
+    /// None when the config asks for unigrams in the text field, which is the plain output
+    pub fn new((min, max): (usize, usize), separator: &str, field: Option<String>) -> Result<Option<Self>> {
+        if min == 0 || min > max {
+            return Err(invalid_input(format!("Invalid n-gram range: ({}, {})", min, max)));
+        }
+        if (min, max) == (1, 1) && field.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(NgramGenerator { min, max, separator: separator.to_string(), field }))
+    }
+
+    /// Every n-gram in the range, shortest first, in text order for each length
+    pub fn generate(&self, tokens: &[Token]) -> Vec<String> {
+        let mut ngrams = Vec::new();
+        for n in self.min..=self.max {
+            for window in tokens.windows(n) {
+                let mut ngram = String::with_capacity(window.iter().map(|t| t.text.len() + self.separator.len()).sum());
+                for token in window {
+                    if !ngram.is_empty() {
+                        ngram.push_str(&self.separator);
+                    }
+                    ngram.push_str(&token.text);
+                }
+                ngrams.push(ngram);
+            }
+        }
+        ngrams
+    }
+}
@@ -1,308 +1,450 @@
-/// norm.rs
-/// 
-/// Provides functions for normalizing text.
+//! norm.rs
+//! 
+//! Provides functions for normalizing text.
 
 use simd_json::OwnedValue;
 use simd_json::prelude::*;
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::io::Result;
-use unidecode::unidecode;
-use std::arch::x86_64::*;
-use crate::set;
-use rust_stemmers::{Algorithm, Stemmer};
-
-fn remove_non_alphanumeric_scalar(text: &mut [u8]) -> () {
-    // Base logic (1 byte at a time)
-    for byte in text.iter_mut() {
-        if !byte.is_ascii_alphanumeric() {
-            *byte = b' ';
-        }
-    }
+use caseless::default_case_fold_str;
+use unicode_segmentation::UnicodeSegmentation;
+use crate::entity::Piece;
+use crate::filter::{TokenFilter, Verdict};
+use crate::html::strip_html;
+use crate::langid;
+use crate::phrase::PhraseStage;
+use crate::quality::QualityAction;
+use crate::rewrite::RewriteStage;
+use crate::token::{join, Token};
+use crate::pipeline::{Lexicon, Mode, Pipeline};
+use crate::segment::{is_complex_script, Segmenter};
+use crate::simd;
+use crate::surface::SurfaceForms;
+use crate::unicode::{normalize_form, strip_diacritics};
+
+thread_local! {
+    /// Working buffers of the ASCII tokenizer (the text as-is and lowercased),
+    /// reused across documents instead of allocated for each one
+    static ASCII_BUFFERS: RefCell<(Vec<u8>, Vec<u8>)> = const { RefCell::new((Vec::new(), Vec::new())) };
 }
 
-#[target_feature(enable = "sse4.1")]
-unsafe fn remove_non_alphanumeric_sse2(text: &mut [u8]) -> () {
-    // SSE2 logic (16 bytes at a time)
-    
-    let ptr : *mut u8 = text.as_mut_ptr();
-    let len: usize = text.len();
-    let mut i = 0;
-
-    unsafe {
-        let space = _mm_set1_epi8(b' ' as i8);
-
-        while i + 16 <= len {
-            // Load 16 bytes from the input
-            let chunk = _mm_loadu_si128(ptr.add(i) as *const __m128i);
-
-            let is_digit = {
-                let digit_min = _mm_set1_epi8(b'0' as i8 - 1);
-                let digit_max = _mm_set1_epi8(b'9' as i8 + 1);
-                let ge_digit_min = _mm_cmpgt_epi8(chunk, digit_min);
-                let le_digit_max = _mm_cmplt_epi8(chunk, digit_max);
-                _mm_and_si128(ge_digit_min, le_digit_max)
-            };
-
-            let is_upper = {
-                let upper_min = _mm_set1_epi8(b'A' as i8 - 1);
-                let upper_max = _mm_set1_epi8(b'Z' as i8 + 1);
-                let ge_upper_min = _mm_cmpgt_epi8(chunk, upper_min);
-                let le_upper_max = _mm_cmplt_epi8(chunk, upper_max);
-                _mm_and_si128(ge_upper_min, le_upper_max)
-            };
-
-            let is_lower = {
-                let lower_min = _mm_set1_epi8(b'a' as i8 - 1);
-                let lower_max = _mm_set1_epi8(b'z' as i8 + 1);
-                let ge_lower_min = _mm_cmpgt_epi8(chunk, lower_min);
-                let le_lower_max = _mm_cmplt_epi8(chunk, lower_max);
-                _mm_and_si128(ge_lower_min, le_lower_max)
-            };
-
-            // Combine the masks
-            let is_alphanumeric = _mm_or_si128(_mm_or_si128(is_digit, is_upper), is_lower);
-
-            // Create a mask for non-alphanumeric characters
-            let non_alphanumeric_mask = _mm_cmpeq_epi8(is_alphanumeric, _mm_setzero_si128());
-
-            let result = _mm_blendv_epi8(chunk, space, non_alphanumeric_mask);
-            // Store the result back to the output
-            _mm_storeu_si128(ptr.add(i) as *mut __m128i, result);
-
-            i += 16;
-        }
-    }
-    
-    
-    // Process remaining bytes
-    for j in i..len {
-        if !text[j].is_ascii_alphanumeric() {
-            text[j] = b' ';
-        }
+/// Adds a word to the token stream
+/// If the word is rejected by the token filters, or is a stopword, it is skipped
+/// 
+/// # Arguments
+/// * `tokens` - The token stream to add the word to
+/// * `word` - The word to add, already lowercased
+/// * `filter` - The length, number and mixed-word filters
+/// * `lexicon` - The stemmer and stopwords of the document language
+fn add_word(tokens : &mut Vec<Token>, word: &[u8], filter : &TokenFilter, lexicon : &Lexicon) {
+    // SAFETY: The word is a slice of the ASCII buffer
+    let word = unsafe { std::str::from_utf8_unchecked(word) };
+    match filter.check(word) {
+        Verdict::Keep => {}
+        Verdict::Drop => return,
+        Verdict::Replace(placeholder) => return tokens.push(Token::verbatim(placeholder.to_string())),
     }
-}
 
-#[target_feature(enable = "avx2")]
-unsafe fn remove_non_alphanumeric_avx2(text: &mut [u8]) -> () {
-    // AVX2 logic (32 bytes at a time)
-    let ptr : *mut u8 = text.as_mut_ptr();
-    let len: usize = text.len();
-    let mut i = 0;
-
-    unsafe {
-        let space = _mm256_set1_epi8(b' ' as i8);
-        while i + 32 <= len {
-            // Load 32 bytes from the input
-            let chunk = _mm256_loadu_si256(ptr.add(i) as *const __m256i);
-
-            let is_digit = {
-                let digit_min = _mm256_set1_epi8(b'0' as i8 - 1); // We subtract 1 to include '0' since simd does not have less than or equal
-                let digit_max = _mm256_set1_epi8(b'9' as i8 + 1); // Same here, we add 1 to include '9'
-                let ge_digit_min = _mm256_cmpgt_epi8(chunk, digit_min); // chunk > '0' - 1
-                let le_digit_max = _mm256_cmpgt_epi8(digit_max, chunk); // chunk < '9' + 1
-                _mm256_and_si256(ge_digit_min, le_digit_max)
-            };
-
-            let is_upper = {
-                let upper_min = _mm256_set1_epi8(b'A' as i8 - 1); // And so on...
-                let upper_max = _mm256_set1_epi8(b'Z' as i8 + 1);
-                let ge_upper_min = _mm256_cmpgt_epi8(chunk, upper_min); // chunk > 'A' - 1
-                let le_upper_max = _mm256_cmpgt_epi8(upper_max, chunk); // chunk < 'Z' + 1
-                _mm256_and_si256(ge_upper_min, le_upper_max)
-            };
-
-            let is_lower = {
-                let lower_min = _mm256_set1_epi8(b'a' as i8 - 1);
-                let lower_max = _mm256_set1_epi8(b'z' as i8 + 1);
-                let ge_lower_min = _mm256_cmpgt_epi8(chunk, lower_min);
-                let le_lower_max = _mm256_cmpgt_epi8(lower_max, chunk);
-                _mm256_and_si256(ge_lower_min, le_lower_max)
-            };
-
-            // Combine the masks
-            let is_alphanumeric = _mm256_or_si256(_mm256_or_si256(is_digit, is_upper), is_lower);
-
-            // Create a mask for non-alphanumeric characters
-            let non_alphanumeric_mask = _mm256_cmpeq_epi8(is_alphanumeric, _mm256_setzero_si256());
-
-            let result = _mm256_blendv_epi8(chunk, space, non_alphanumeric_mask);
-            // Store the result back to the output
-            _mm256_storeu_si256(ptr.add(i) as *mut __m256i, result);
-
-            i += 32;
-        }
-    }
+    // Borrowed from the lowercase buffer, so that stopwords (a good half of the words) never allocate
+    push_word(tokens, Cow::Borrowed(word), lexicon);
+}
 
-    if i + 16 <= len {
-        // If there are 16 bytes left, use SSE2 to process them
-        unsafe { remove_non_alphanumeric_sse2(&mut text[i..]) };
-        i += 16;
+/// Unicode counterpart of `add_word`
+/// Lengths are counted in characters and the word is case folded here
+fn add_unicode_word(tokens : &mut Vec<Token>, word: &str, filter : &TokenFilter, lexicon : &Lexicon) {
+    // Numbers are recognized in any script
+    match filter.check(word) {
+        Verdict::Keep => {}
+        Verdict::Drop => return,
+        Verdict::Replace(placeholder) => return tokens.push(Token::verbatim(placeholder.to_string())),
     }
 
-    // Process remaining bytes
-    for j in i..len {
-        if !text[j].is_ascii_alphanumeric() {
-            text[j] = b' ';
-        }
-    }
+    push_word(tokens, Cow::Owned(default_case_fold_str(word)), lexicon);
 }
 
-// TODO: avx512 (my machine doesn't support it so I can't test it)
-
-/// Router for the remove_non_alphanumeric function
-/// Uses the best available SIMD instruction set (excluding AVX512 for now)
-fn remove_non_alphanumeric(text: &mut [u8]) -> () {
-    // Call the SSE2 function
-    if is_x86_feature_detected!("avx2") {
-        unsafe { remove_non_alphanumeric_avx2(text) }
-    } else
-    if is_x86_feature_detected!("sse4.1") {
-        // Fallback to the AVX2 logic
-        unsafe { remove_non_alphanumeric_sse2(text) }
-    } else {
-        // Fallback to the base logic
-        remove_non_alphanumeric_scalar(text)
+/// Drops stopwords and pushes whatever is left to the token stream
+/// Expects the word to be lowercased (or case folded) already
+fn push_word(tokens : &mut Vec<Token>, word: Cow<str>, lexicon : &Lexicon) {
+    if lexicon.stopwords.is_some_and(|stopwords| stopwords.contains(word.as_bytes())) {
+        return;
     }
-}
 
+    tokens.push(Token::word(word.into_owned()));
+}
 
-const SPACE : u8 = b' ';
-
-/// Adds a word to the base string
-/// If the word is empty, too short, or a stopword, it is skipped
+/// Tokenizes a string into words
 /// 
 /// # Arguments
-/// * `base_string` - The base string to add the word to
-/// * `word` - The word to add
-/// * `stemmer` - The stemmer to use (rust-stemmers, porter2)
-fn add_word(base_string : &mut String, word: &[u8], stemmer : &Stemmer) -> () {
-    if word.is_empty() || word.len() < 2 {
-        return;
-    }
+/// * `tokens` - The token stream, words are appended to it
+/// * `text` - The text to tokenize, non-alphanumeric characters already turned into spaces
+/// * `lowercase` - The same text, lowercased
+/// * `pipeline` - The pipeline holding the identifier splitting rules
+/// * `lexicon` - The stemmer and stopwords of the document language
+/// 
+/// # Warning
+/// * This function expects the buffers to be valid ASCII strings of the same length.
+///   If they are not, we get undefined behavior
+fn split_ascii_words(tokens: &mut Vec<Token>, text: &[u8], lowercase: &[u8], pipeline : &Pipeline, lexicon : &Lexicon) {
 
-    // Ignore if all the characters are numeric
-    if word.iter().all(|&c| c.is_ascii_digit()) {
-        return;
-    }
+    // SAFETY: The buffer only holds ASCII at this point
+    let text : &str = unsafe { std::str::from_utf8_unchecked(text) };
 
-    let lowercase = word.to_ascii_lowercase(); // TODO: In-place this
-    if set::STOPWORDS.contains(&lowercase) {
-        return;
-    }
+    simd::for_each_word(text.as_bytes(), |start, end| {
+        let word = &text[start..end];
+        if pipeline.filter.rejects(word) {
+            return;
+        }
 
-    // Stem
+        // Without capitals (most words) only the digit rule can split the word
+        let lower = &lowercase[start..end];
+        if word.as_bytes() == lower && !(pipeline.identifiers.digits && lower.iter().any(u8::is_ascii_digit)) {
+            return add_word(tokens, lower, &pipeline.filter, lexicon);
+        }
 
-    let as_str : &str = unsafe { std::str::from_utf8_unchecked(&lowercase) };
-    let stemmed = stemmer.stem(as_str);
+        // Split camelCase, acronyms... on the original case and add the lowercase of each part
+        pipeline.identifiers.split(word, |part| {
+            let offset = part.as_ptr() as usize - word.as_ptr() as usize;
+            add_word(tokens, &lower[offset..offset + part.len()], &pipeline.filter, lexicon)
+        });
+    });
+}
 
-    base_string.push_str(stemmed.as_ref());
-    base_string.push(SPACE as char);
+/// Adds the words of an already segmented Unicode text
+/// Letters from every script are kept, the segmenter is expected to have
+/// dropped segments without any alphanumeric character
+///
+/// # Arguments
+/// * `tokens` - The token stream, words are appended to it
+/// * `words` - The words, either from `unicode_words` (UAX #29) or the dictionary segmenter
+/// * `pipeline` - The pipeline holding the identifier splitting rules
+/// * `lexicon` - The stemmer and stopwords of the document language
+fn split_unicode_words<'a>(tokens: &mut Vec<Token>, words: impl Iterator<Item = &'a str>, pipeline : &Pipeline, lexicon : &Lexicon) {
+
+    for word in words.filter(|word| !pipeline.filter.rejects(word)) {
+        // Same identifier rules as the ASCII tokenizer
+        pipeline.identifiers.split(word, |part| add_unicode_word(tokens, part, &pipeline.filter, lexicon));
+    }
 }
 
-/// Tokenizes a string into words
-/// 
+/// Stems every word of the token stream in place, unless the pipeline has an exception for it
+/// Verbatim tokens (protected terms, placeholders, phrases) are left alone
+///
 /// # Arguments
-/// * `text` - The text to tokenize
-/// 
-/// # Warning
-/// * It is not safe to use the buffer after this function is called
-/// * This function expects the buffer to be a valid ASCII string.
-/// If the string is not valid ASCII, we get undefined behavior
-fn stem_text(text: &mut Vec<u8>) -> String {
+/// * `tokens` - The token stream
+/// * `pipeline` - The pipeline holding the stemmer exceptions
+/// * `lexicon` - The stemmer of the document language
+/// * `surface` - Records which word produced each stem, when given
+pub fn stem_tokens(tokens : &mut [Token], pipeline : &Pipeline, lexicon : &Lexicon, mut surface : Option<&mut SurfaceForms>) {
+    // Stems are copied into a spare buffer which is then swapped with the word,
+    // so that the buffers of the words are recycled instead of allocating one per stem
+    let mut spare = String::new();
+    for token in tokens.iter_mut().filter(|t| t.is_word()) {
+        let mut replace = |stem : &str| {
+            if let Some(surface) = surface.as_deref_mut() {
+                surface.add(stem, &token.text);
+            }
+            if stem == token.text {
+                return false;
+            }
+            spare.clear();
+            spare.push_str(stem);
+            true
+        };
+        let changed = match pipeline.stem_exceptions.get(&token.text) {
+            Some(stem) => replace(stem),
+            None => lexicon.with_stem(&token.text, replace),
+        };
+        if changed {
+            std::mem::swap(&mut token.text, &mut spare);
+        }
+    }
+}
 
-    let mut tokenized_text = String::with_capacity(text.len());
-    let stemmer = Stemmer::create(Algorithm::English);
+/// Tokenizes a text mixing complex scripts (CJK/Thai) and space-delimited ones in ascii mode
+/// Each dictionary word of a complex script is transliterated on its own and
+/// emitted as one verbatim token, since the stemmer and stopwords of the document
+/// language do not apply to it; the text in between goes through the ASCII tokenizer
+///
+/// # Arguments
+/// * `tokens` - The token stream, words are appended to it
+/// * `text` - The text to tokenize
+/// * `segmenter` - The dictionary segmenter
+/// * `pipeline` - The compiled normalization pipeline
+/// * `lexicon` - The stemmer and stopwords of the document language
+fn tokenize_segmented_ascii(tokens : &mut Vec<Token>, text : &str, segmenter : &Segmenter, pipeline : &Pipeline, lexicon : &Lexicon) {
+    // Start of the text not tokenized yet, and end of the current segment
     let mut start = 0;
-    let mut in_word = false;
-    for (i, &byte) in text.iter().enumerate() {
-        if byte != SPACE && !in_word {
-            // Start of a new word
-            in_word = true;
-            start = i;
-        } else if byte == SPACE && in_word {
-            // End of a word
-
-            // Skip words that are too short
-            if i - start < 2 {
-                in_word = false;
-                continue;
-            }
+    let mut end = 0;
+    for (segment, is_word) in segmenter.segments(text) {
+        end += segment.len();
+        if !is_word || !segment.chars().any(is_complex_script) {
+            continue;
+        }
 
-            // Check if the word is camelCase
-            for j in start..i-1 {
-                if text[j].is_ascii_lowercase() && text[j+1].is_ascii_uppercase() {
-                    // If the word is camelCase, split it
-                    {
-                        add_word(&mut tokenized_text, &text[start..j+1], &stemmer);
-                    }
-                    // Continue and let the next iteration handle the rest
-                    start = j + 1;
-                }
-            }
-            
-            // Add the word to the tokenized text
-            {
-                add_word(&mut tokenized_text, &text[start..i], &stemmer);
-            }
-            in_word = false;
+        tokenize_ascii(tokens, &text[start..end - segment.len()], pipeline, lexicon);
+        start = end;
 
+        // Glue the romanized syllables of the word back together
+        let ascii = pipeline.transliterator.transliterate(segment);
+        let word : String = ascii.chars().filter(char::is_ascii_alphanumeric).map(|c| c.to_ascii_lowercase()).collect();
+        if !word.is_empty() {
+            tokens.push(Token::verbatim(word));
         }
     }
-
-    tokenized_text
+    tokenize_ascii(tokens, &text[start..], pipeline, lexicon);
 }
 
 /// Public function to normalize text in a JSON object in-place.
+/// Also tags the object with its detected language, n-grams and quality scores when the pipeline asks for it.
 /// 
 /// # Arguments
 /// * `val` - The JSON object to normalize
 /// * `k` - The key to normalize
+/// * `pipeline` - The compiled normalization pipeline
+/// * `surface` - Collects the stem → word counts, when given
 /// 
 /// # Returns
-/// * `Result<()>` - Ok if successful, Err if there was an error
-pub fn normalize_owned_value(val : &mut OwnedValue, k : &str) -> Result<()>{
+/// * `Result<bool>` - Whether the record is kept (it passes the quality filters, or they only annotate),
+///   Err if there was an error
+pub fn normalize_owned_value(val : &mut OwnedValue, k : &str, pipeline : &Pipeline, surface : Option<&mut SurfaceForms>) -> Result<bool>{
 
     let text = match val.get_str(k) {
         Some(text) => text,
-        None => return Ok(()), // If the key doesn't exist, do nothing
+        None => return Ok(true), // If the key doesn't exist, do nothing
     };
 
-    let norm_text = _normalize_text(text)?;
+    let text = preprocess(text, pipeline);
+    let detection = if pipeline.detects_language() { langid::detect(&text) } else { None };
+    let detected = pipeline.detected_lexicon(detection.as_ref());
+    let lexicon = detected.unwrap_or(&pipeline.lexicon);
+    // Measured before normalization, which removes the stopwords and punctuation they look at
+    let scores = pipeline.quality.as_ref().map(|quality| quality.measure(&text, lexicon.stopwords));
+    let tokens = normalize_with_lexicon(&text, pipeline, lexicon, surface)?;
 
     // SAFETY: We are replacing the text in the JSON object with a new string
     // and the JSON object is mutable
-    match val.insert(k, norm_text) {
+    insert(val, k, output_text(&tokens, pipeline))?;
+
+    if let Some(ngrams) = &pipeline.ngrams
+        && let Some(field) = &ngrams.field {
+        insert(val, field, ngrams.generate(&tokens))?;
+    }
+
+    if let Some(field) = &pipeline.language_field {
+        // Undetectable text (empty, digits only...) is tagged as unknown
+        let code = detection.as_ref().map_or("und", |d| d.code);
+        insert(val, field, code)?;
+    }
+    if let Some(field) = &pipeline.language_confidence_field {
+        insert(val, field, detection.as_ref().map_or(0.0, |d| d.confidence))?;
+    }
+
+    let (Some(quality), Some(scores)) = (&pipeline.quality, scores) else {
+        return Ok(true);
+    };
+    let passes = quality.passes(&scores);
+    if let Some(field) = &quality.field {
+        let mut object = OwnedValue::object();
+        for (metric, score) in scores {
+            insert(&mut object, &metric.name(), score)?;
+        }
+        insert(&mut object, "pass", passes)?;
+        insert(val, field, object)?;
+    }
+    Ok(passes || quality.action == QualityAction::Annotate)
+}
+
+pub fn insert(val : &mut OwnedValue, k : &str, v : impl Into<OwnedValue>) -> Result<()> {
+    match val.insert(k, v) {
         Ok(_) => Ok(()),
-        Err(e) => Err(std::io::Error::new(std::io::ErrorKind::Other, format!("simd_json insert error: {:?}", e))),
+        Err(e) => Err(std::io::Error::other(format!("simd_json insert error: {:?}", e))),
     }
+}
 
+/// Normalizes a &str
+/// Uses the detected language of the text when the pipeline has auto_language on
+///
+/// # Arguments
+/// * `text` - The text to normalize
+/// * `pipeline` - The compiled normalization pipeline
+/// 
+/// # Returns
+/// * `Result<String>` - The normalized string (or its n-grams, unless they go to a field)
+pub fn _normalize_text(text : &str, pipeline : &Pipeline) -> Result<String> {
+    let text = preprocess(text, pipeline);
+    let detected = if pipeline.auto_language {
+        pipeline.detected_lexicon(langid::detect(&text).as_ref())
+    } else {
+        None
+    };
+    let tokens = normalize_with_lexicon(&text, pipeline, detected.unwrap_or(&pipeline.lexicon), None)?;
+    Ok(output_text(&tokens, pipeline))
+}
+
+/// Cleanup steps that run before language detection and tokenization
+/// (raw rewrite rules, markup, Unicode forms, diacritics), each one borrows when there is nothing to do
+fn preprocess<'a>(text : &'a str, pipeline : &Pipeline) -> Cow<'a, str> {
+    let mut text : Cow<str> = match pipeline.rewriter.as_ref().filter(|r| r.stage == RewriteStage::Raw) {
+        Some(rewriter) => rewriter.apply(text),
+        None => Cow::Borrowed(text),
+    };
+    if pipeline.strip_html
+        && let Cow::Owned(stripped) = strip_html(&text) {
+        text = Cow::Owned(stripped);
+    }
+    if let Some(form) = pipeline.unicode_form
+        && let Cow::Owned(normalized) = normalize_form(&text, form) {
+        text = Cow::Owned(normalized);
+    }
+    if pipeline.strip_diacritics
+        && let Cow::Owned(stripped) = strip_diacritics(&text) {
+        text = Cow::Owned(stripped);
+    }
+    text
 }
 
-/// Normalizes a &str in-place
+/// Normalizes an already preprocessed &str with a resolved language
 ///
 /// # Arguments
-/// * `val` - The JSON object to normalize
+/// * `text` - The text to normalize
+/// * `pipeline` - The compiled normalization pipeline
+/// * `lexicon` - The stemmer and stopwords of the document language
+/// * `surface` - Collects the stem → word counts, when given
 /// 
 /// # Returns
-/// * `Result<String>` - The normalized string
-pub fn _normalize_text(text : &str) -> Result<String> {
-    
-    // Turn text into ascii and then into a byte vector
-    let ascii_text : String = unidecode(text);
-    let mut ascii_vec : Vec<u8> = ascii_text.into_bytes();
+/// * `Result<Vec<Token>>` - The final token stream
+fn normalize_with_lexicon(text : &str, pipeline : &Pipeline, lexicon : &Lexicon, surface : Option<&mut SurfaceForms>) -> Result<Vec<Token>> {
 
-    // We can use simd instructions to do SOME of the work
+    let mut tokens = collect_tokens(text, pipeline, lexicon);
 
-    // Turn non-alphanumeric characters into spaces
-    remove_non_alphanumeric(&mut ascii_vec);
+    if let Some(synonyms) = &pipeline.synonyms {
+        synonyms.map(&mut tokens);
+    }
+    if let Some(phrases) = pipeline.phrases.as_ref().filter(|p| p.stage == PhraseStage::BeforeStemming) {
+        phrases.merge(&mut tokens);
+    }
+    stem_tokens(&mut tokens, pipeline, lexicon, surface);
+    if let Some(phrases) = pipeline.phrases.as_ref().filter(|p| p.stage == PhraseStage::AfterStemming) {
+        phrases.merge(&mut tokens);
+    }
 
-    let mut tokenized : String = stem_text(&mut ascii_vec);
-    // Remove the last space
-    tokenized.pop();
+    Ok(tokens)
+}
 
+/// The normalized text: the tokens, or their n-grams when they do not go to a field of their own
+fn output_text(tokens : &[Token], pipeline : &Pipeline) -> String {
+    match pipeline.ngrams.as_ref().filter(|ngrams| ngrams.field.is_none()) {
+        Some(ngrams) => ngrams.generate(tokens).join(" "),
+        None => join(tokens),
+    }
+}
 
-    // Turn multiple spaces into a single space
-    // SAFETY: This is safe because the utf character set is a superset of ascii
-    // And we are not adding non-ascii characters to the string
-    // Handle the Result from insert; propagate any error
-    Ok(tokenized)
-}
\ No newline at end of file
+/// Turns a preprocessed text into tokens, up to and including stopword removal
+///
+/// # Arguments
+/// * `text` - The text to tokenize
+/// * `pipeline` - The compiled normalization pipeline
+/// * `lexicon` - The stemmer and stopwords of the document language
+///
+/// # Returns
+/// * `Vec<Token>` - Lowercased words and verbatim tokens, in order
+pub fn collect_tokens(text : &str, pipeline : &Pipeline, lexicon : &Lexicon) -> Vec<Token> {
+    // Roughly one word every 8 bytes, so that the stream rarely grows
+    let mut tokens = Vec::with_capacity(text.len() / 8);
+    let synonym_phrases = pipeline.synonyms.as_ref().and_then(|s| s.phrases.as_ref());
+    if pipeline.protected.is_none() && synonym_phrases.is_none() && pipeline.entities.is_none() {
+        tokenize(&mut tokens, text, pipeline, lexicon);
+    } else {
+        for piece in pretokenize(text, pipeline) {
+            match piece {
+                Piece::Text(chunk) => tokenize(&mut tokens, chunk, pipeline, lexicon),
+                Piece::Token(token) => tokens.push(Token::verbatim(token.to_string())),
+                Piece::Canonical(words) => tokens.extend(words.split_whitespace().map(|word| Token::word(word.to_string()))),
+            }
+        }
+    }
+    tokens
+}
+
+/// Splits out protected tokens, multiword synonyms and entities, which all bypass the tokenizer
+/// User-supplied terms go first so that they win over the recognizers
+fn pretokenize<'a>(text : &'a str, pipeline : &'a Pipeline) -> Vec<Piece<'a>> {
+    let mut pieces = match &pipeline.protected {
+        Some(protected) => protected.split(text),
+        None => vec![Piece::Text(text)],
+    };
+    if let Some(synonyms) = pipeline.synonyms.as_ref().and_then(|s| s.phrases.as_ref()) {
+        pieces = split_text_pieces(pieces, |chunk| synonyms.split(chunk));
+    }
+    if let Some(recognizer) = &pipeline.entities {
+        pieces = split_text_pieces(pieces, |chunk| recognizer.split(chunk));
+    }
+    pieces
+}
+
+/// Splits the text pieces further, leaving tokens alone
+fn split_text_pieces<'a>(pieces : Vec<Piece<'a>>, split : impl Fn(&'a str) -> Vec<Piece<'a>>) -> Vec<Piece<'a>> {
+    pieces
+        .into_iter()
+        .flat_map(|piece| match piece {
+            Piece::Text(chunk) => split(chunk),
+            token => vec![token],
+        })
+        .collect()
+}
+
+/// Tokenizes a chunk of text, appending the words to the token stream
+///
+/// # Arguments
+/// * `tokens` - The token stream, words are appended to it
+/// * `text` - The text to tokenize
+/// * `pipeline` - The compiled normalization pipeline
+/// * `lexicon` - The stemmer and stopwords of the document language
+fn tokenize(tokens : &mut Vec<Token>, text : &str, pipeline : &Pipeline, lexicon : &Lexicon) {
+    match pipeline.mode {
+        // Text without spaces between words has to be segmented before transliteration
+        Mode::Ascii => match pipeline.segmenter.as_ref().filter(|s| s.applies_to(text)) {
+            Some(segmenter) => tokenize_segmented_ascii(tokens, text, segmenter, pipeline, lexicon),
+            None => tokenize_ascii(tokens, text, pipeline, lexicon),
+        },
+        Mode::Unicode => match pipeline.segmenter.as_ref().filter(|s| s.applies_to(text)) {
+            Some(segmenter) => split_unicode_words(tokens, segmenter.words(text), pipeline, lexicon),
+            None => split_unicode_words(tokens, text.unicode_words(), pipeline, lexicon),
+        },
+    }
+}
+
+/// Transliterates a chunk of text to ASCII and tokenizes it
+///
+/// # Arguments
+/// * `tokens` - The token stream, words are appended to it
+/// * `text` - The text to tokenize
+/// * `pipeline` - The compiled normalization pipeline
+/// * `lexicon` - The stemmer and stopwords of the document language
+fn tokenize_ascii(tokens : &mut Vec<Token>, text : &str, pipeline : &Pipeline, lexicon : &Lexicon) {
+    // Turn text into ascii, pure ASCII text (most English web data) is borrowed as-is
+    let mut ascii_text = match simd::is_ascii(text.as_bytes()) {
+        true => Cow::Borrowed(text),
+        false => pipeline.transliterator.transliterate(text),
+    };
+    if let Some(rewriter) = pipeline.rewriter.as_ref().filter(|r| r.stage == RewriteStage::Ascii)
+        && let Cow::Owned(rewritten) = rewriter.apply(&ascii_text) {
+        // Replacements may bring non-ASCII back, it is turned into spaces below
+        ascii_text = Cow::Owned(rewritten);
+    }
+
+    ASCII_BUFFERS.with_borrow_mut(|(ascii_vec, lowercase)| {
+        ascii_vec.clear();
+        ascii_vec.extend_from_slice(ascii_text.as_bytes());
+
+        // We can use simd instructions to do SOME of the work
+
+        // Turn non-alphanumeric characters into spaces
+        simd::remove_non_alphanumeric(ascii_vec);
+        // Lowercase the whole text at once rather than word by word
+        simd::lowercase_into(ascii_vec, lowercase);
+
+        split_ascii_words(tokens, ascii_vec, lowercase, pipeline, lexicon)
+    })
+}
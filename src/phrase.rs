@@ -0,0 +1,157 @@
+//! phrase.rs
+//!
+//! Provides phrase merging: known multiword expressions ("new york",
+//! "machine learning") become single tokens (new_york) for topic models and BM25.
+//! The phrase list is read from a file, one phrase per line, and each line is
+//! run through the same pipeline as the documents so that both sides match.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Result;
+use std::path::Path;
+use crate::norm::{collect_tokens, stem_tokens};
+use crate::pipeline::{invalid_input, Pipeline};
+use crate::token::Token;
+
+/// Where phrases are merged, relative to stemming
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PhraseStage {
+    /// Merge surface words (new_york), the phrase is not stemmed
+    BeforeStemming,
+    /// Merge stems (machin_learn)
+    AfterStemming,
+}
+
+impl PhraseStage {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "before_stemming" => Ok(PhraseStage::BeforeStemming),
+            "after_stemming" => Ok(PhraseStage::AfterStemming),
+            _ => Err(invalid_input(format!("Unknown phrase stage: {}", name))),
+        }
+    }
+}
+
+pub struct PhraseMerger {
+    pub stage: PhraseStage,
+    separator: String,
+    /// First word → remaining words of every phrase starting with it, longest first
+    phrases: HashMap<String, Vec<Vec<String>>>,
+}
+
+impl PhraseMerger {
+    /// Loads the phrase list, normalizing every line with the given pipeline
+    /// (which must not merge phrases itself)
+    pub fn load(path: &Path, stage: PhraseStage, separator: &str, pipeline: &Pipeline) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+
+        let mut phrases: HashMap<String, Vec<Vec<String>>> = HashMap::new();
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut tokens = collect_tokens(line, pipeline, &pipeline.lexicon);
+            if let Some(synonyms) = &pipeline.synonyms {
+                synonyms.map(&mut tokens);
+            }
+            if stage == PhraseStage::AfterStemming {
+                stem_tokens(&mut tokens, pipeline, &pipeline.lexicon, None);
+            }
+            // Stopwords and filters may leave a single word, which is not a phrase
+            if tokens.len() < 2 || !tokens.iter().all(Token::is_word) {
+                continue;
+            }
+
+            let mut words = tokens.into_iter().map(|t| t.text);
+            let first = words.next().unwrap_or_default();
+            phrases.entry(first).or_default().push(words.collect());
+        }
+
+        for candidates in phrases.values_mut() {
+            candidates.sort_by_key(|rest| std::cmp::Reverse(rest.len()));
+            candidates.dedup();
+        }
+
+        Ok(PhraseMerger { stage, separator: separator.to_string(), phrases })
+    }
+
+    /// Merges every phrase found in the tokens, longest match first
+    pub fn merge(&self, tokens: &mut Vec<Token>) {
+        // Tokens are moved to a new Vec, so that a match does not shift the ones after it
+        let mut merged = Vec::with_capacity(tokens.len());
+        let mut rest = std::mem::take(tokens).into_iter();
+        loop {
+            match self.match_len(rest.as_slice()) {
+                Some(len) => {
+                    let words: Vec<String> = rest.by_ref().take(len).map(|t| t.text).collect();
+                    merged.push(Token::verbatim(words.join(&self.separator)));
+                }
+                None => match rest.next() {
+                    Some(token) => merged.push(token),
+                    None => break,
+                },
+            }
+        }
+        *tokens = merged;
+    }
+
+    /// Number of tokens covered by the longest phrase starting at the first token
+    fn match_len(&self, tokens: &[Token]) -> Option<usize> {
+        let first = tokens.first().filter(|t| t.is_word())?;
+        let candidates = self.phrases.get(&first.text)?;
+        candidates
+            .iter()
+            .find(|rest| {
+                rest.len() < tokens.len()
+                    && rest.iter().zip(&tokens[1..]).all(|(word, t)| t.is_word() && *word == t.text)
+            })
+            .map(|rest| rest.len() + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::NormalizerConfig;
+    use crate::norm::_normalize_text;
+    use crate::pipeline::Pipeline;
+
+    const PHRASES: &str = "new york\nnew york city\nyork city hall\n# comment\nmachine learning\n";
+
+    fn normalize(stage: &str, texts: &[&str]) -> Vec<String> {
+        let path = std::env::temp_dir().join(format!("sstn-phrases-{}-{}.txt", stage, std::process::id()));
+        std::fs::write(&path, PHRASES).unwrap();
+        let config = NormalizerConfig {
+            phrases_file: Some(path.to_string_lossy().into_owned()),
+            phrase_stage: stage.to_string(),
+            ..Default::default()
+        };
+        let pipeline = Pipeline::new(&config).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        texts.iter().map(|text| _normalize_text(text, &pipeline).unwrap()).collect()
+    }
+
+    #[test]
+    fn merges_longest_match_first() {
+        assert_eq!(
+            normalize("before_stemming", &["New York City is big", "New York is big", "new york new york city"]),
+            ["new_york_city big", "new_york big", "new_york new_york_city"]
+        );
+    }
+
+    #[test]
+    fn overlapping_phrases_go_left_to_right() {
+        // "new york city" is merged first, what is left of "york city hall" is not a phrase
+        assert_eq!(
+            normalize("before_stemming", &["York City Hall", "New York City Hall"]),
+            ["york_city_hall", "new_york_city hall"]
+        );
+    }
+
+    #[test]
+    fn merges_surface_words_or_stems() {
+        let texts = ["Machine learning rocks", "Machines learned to rock"];
+        assert_eq!(normalize("before_stemming", &texts), ["machine_learning rock", "machin learn rock"]);
+        assert_eq!(normalize("after_stemming", &texts), ["machin_learn rock", "machin_learn rock"]);
+    }
+}
@@ -0,0 +1,283 @@
+//! pipeline.rs
+//!
+//! Compiles a `NormalizerConfig` into a `Pipeline`, the read-only state
+//! shared by every worker while normalizing.
+//! Anything expensive (stemmers, lookup tables) should be built here once,
+//! not per document.
+
+use std::collections::HashMap;
+use std::io::{self, Result};
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use caseless::default_case_fold_str;
+use rust_stemmers::Algorithm;
+use crate::config::NormalizerConfig;
+use crate::dedup::{DedupMethod, DedupMode};
+use crate::entity::EntityRecognizer;
+use crate::filter::{Numbers, TokenFilter};
+use crate::hashing::FeatureHasher;
+use crate::ident::IdentifierSplitter;
+use crate::langid::Detection;
+use crate::minhash::MinHasher;
+use crate::ngram::NgramGenerator;
+use crate::phrase::{PhraseMerger, PhraseStage};
+use crate::protect::ProtectedTokens;
+use crate::quality::QualityFilters;
+use crate::rewrite::{RewriteStage, Rewriter};
+use crate::segment::{Segmentation, Segmenter};
+use crate::set;
+use crate::stem::{with_cached_stem, StemmerKind, WordStemmer};
+use crate::synonym::Synonyms;
+use crate::unicode::{Form, Transliterator};
+
+/// How the text is turned into words
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    /// Transliterate to ASCII and split on non-alphanumeric characters
+    Ascii,
+    /// Keep every script, split on Unicode word boundaries and case fold
+    Unicode,
+}
+
+impl Mode {
+    fn parse(name: &str) -> Result<Self> {
+        match name {
+            "ascii" => Ok(Mode::Ascii),
+            "unicode" => Ok(Mode::Unicode),
+            _ => Err(invalid_input(format!("Unknown mode: {}", name))),
+        }
+    }
+}
+
+/// Identifies each lexicon, so that per-thread stem caches are never shared between two of them
+static NEXT_LEXICON_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The language-specific parts of the pipeline
+pub struct Lexicon {
+    pub language: Algorithm,
+    pub stemmer: WordStemmer,
+    pub stopwords: Option<&'static set::StopwordSet>,
+    id: usize,
+    /// Size of the per-thread stem cache, None disables it
+    cache_size: Option<NonZeroUsize>,
+}
+
+impl Lexicon {
+    pub fn new(language: Algorithm, stemmer: StemmerKind, cache_size: usize) -> Self {
+        Lexicon {
+            language,
+            stemmer: WordStemmer::new(stemmer, language),
+            stopwords: set::stopwords(language),
+            id: NEXT_LEXICON_ID.fetch_add(1, Ordering::Relaxed),
+            cache_size: NonZeroUsize::new(cache_size),
+        }
+    }
+
+    /// Runs `f` on the stem of a word, going through the stem cache when there is one
+    pub fn with_stem<R>(&self, word: &str, f: impl FnOnce(&str) -> R) -> R {
+        match self.cache_size {
+            Some(capacity) => with_cached_stem(&self.stemmer, self.id, capacity, word, f),
+            None => f(&self.stemmer.stem(word)),
+        }
+    }
+}
+
+pub struct Pipeline {
+    pub strip_html: bool,
+    pub mode: Mode,
+    /// Used when no language is detected (or detection is off)
+    pub lexicon: Lexicon,
+    /// One lexicon per stemmer language, built upfront when auto_language is on
+    pub detected_lexicons: Vec<Lexicon>,
+    /// Word → stem, consulted before the stemmer
+    pub stem_exceptions: HashMap<String, String>,
+    pub unicode_form: Option<Form>,
+    pub strip_diacritics: bool,
+    pub transliterator: Transliterator,
+    pub rewriter: Option<Rewriter>,
+    pub identifiers: IdentifierSplitter,
+    pub filter: TokenFilter,
+    pub segmenter: Option<Segmenter>,
+    /// Pick the stemmer and stopwords from the detected language of each document
+    pub auto_language: bool,
+    pub language_field: Option<String>,
+    pub language_confidence_field: Option<String>,
+    pub dedup: DedupMode,
+    pub duplicate_field: String,
+    pub hash_field: Option<String>,
+    pub dedup_method: DedupMethod,
+    pub minhash: Option<MinHasher>,
+    pub minhash_field: Option<String>,
+    pub cluster_field: Option<String>,
+    pub simhash_field: Option<String>,
+    pub quality: Option<QualityFilters>,
+    pub entities: Option<EntityRecognizer>,
+    pub protected: Option<ProtectedTokens>,
+    pub synonyms: Option<Synonyms>,
+    pub phrases: Option<PhraseMerger>,
+    pub ngrams: Option<NgramGenerator>,
+    pub hasher: Option<FeatureHasher>,
+}
+
+impl Pipeline {
+    pub fn new(config: &NormalizerConfig) -> Result<Self> {
+        let mode = Mode::parse(&config.mode)?;
+        let language = parse_language(&config.language)?;
+        let stemmer = StemmerKind::parse(&config.stemmer)?;
+        if stemmer.is_english_only() && language != Algorithm::English {
+            return Err(invalid_input(format!("The {} stemmer only supports English", config.stemmer)));
+        }
+        let unicode_form = config.unicode_form.as_deref().map(Form::parse).transpose()?;
+        let phrase_stage = PhraseStage::parse(&config.phrase_stage)?;
+        let rewrite_stage = RewriteStage::parse(&config.rewrite_stage)?;
+        if rewrite_stage == RewriteStage::Ascii && mode != Mode::Ascii {
+            return Err(invalid_input("The \"ascii\" rewrite stage requires \"ascii\" mode".to_string()));
+        }
+
+        let dedup_method = DedupMethod::parse(&config.dedup_method)?;
+        let minhash = MinHasher::new(config.minhash_permutations, config.shingle_size, config.minhash_seed, config.lsh_bands)?;
+        if config.minhash_field.is_some() && minhash.is_none() {
+            return Err(invalid_input("minhash_field requires minhash_permutations".to_string()));
+        }
+        let clusters = minhash.as_ref().is_some_and(|minhash| minhash.bands > 0);
+        if (config.cluster_field.is_some() || dedup_method == DedupMethod::MinHash) && !clusters {
+            return Err(invalid_input("Clustering near duplicates requires minhash_permutations and lsh_bands".to_string()));
+        }
+
+        let mut pipeline = Pipeline {
+            strip_html: config.strip_html,
+            mode,
+            lexicon: Lexicon::new(language, stemmer, config.stem_cache_size),
+            detected_lexicons: match config.auto_language {
+                true => LANGUAGES.iter().map(|&l| Lexicon::new(l, stemmer, config.stem_cache_size)).collect(),
+                false => Vec::new(),
+            },
+            stem_exceptions: HashMap::new(),
+            unicode_form,
+            strip_diacritics: config.strip_diacritics,
+            transliterator: Transliterator::parse(&config.transliterator)?,
+            rewriter: Rewriter::new(&config.rewrite_rules, rewrite_stage)?,
+            identifiers: IdentifierSplitter {
+                camel_case: config.split_camel_case,
+                acronyms: config.split_acronyms,
+                digits: config.split_digits,
+                snake_case: config.split_snake_case,
+            },
+            filter: TokenFilter {
+                min_length: config.min_token_length,
+                max_length: config.max_token_length,
+                numbers: Numbers::parse(&config.numbers, &config.number_placeholder)?,
+                drop_mixed: config.drop_alphanumeric,
+            },
+            segmenter: Segmenter::new(Segmentation::parse(&config.segmentation)?),
+            auto_language: config.auto_language,
+            language_field: config.language_field.clone(),
+            language_confidence_field: config.language_confidence_field.clone(),
+            dedup: DedupMode::parse(&config.dedup)?,
+            duplicate_field: config.duplicate_field.clone(),
+            hash_field: config.hash_field.clone(),
+            dedup_method,
+            minhash,
+            minhash_field: config.minhash_field.clone(),
+            cluster_field: config.cluster_field.clone(),
+            simhash_field: config.simhash_field.clone(),
+            quality: QualityFilters::new(&config.quality_filters, &config.quality_action, config.quality_field.clone())?,
+            entities: EntityRecognizer::new(&config.entities)?,
+            protected: ProtectedTokens::new(&config.protected_tokens, config.protected_case_insensitive)?,
+            synonyms: None,
+            phrases: None,
+            ngrams: NgramGenerator::new(config.ngram_range, &config.ngram_separator, config.ngram_field.clone())?,
+            hasher: FeatureHasher::new(
+                config.hashing_features,
+                config.hashing_seed,
+                &config.hashing_weights,
+                config.idf_file.as_deref(),
+                config.hashing_normalize,
+                config.hashing_field.clone(),
+                config.hashing_file,
+            )?,
+        };
+
+        pipeline.stem_exceptions = config.stem_exceptions
+            .iter()
+            .map(|(word, stem)| (pipeline.token_key(word), pipeline.token_key(stem)))
+            .collect();
+
+        // Synonyms and phrases are normalized by the pipeline itself, so they are loaded last
+        // (phrases after synonyms, so that they are written in canonical form)
+        if let Some(path) = &config.synonyms_file {
+            pipeline.synonyms = Some(Synonyms::load(Path::new(path), &pipeline)?);
+        }
+        if let Some(path) = &config.phrases_file {
+            let phrases = PhraseMerger::load(Path::new(path), phrase_stage, &config.phrase_separator, &pipeline)?;
+            pipeline.phrases = Some(phrases);
+        }
+        Ok(pipeline)
+    }
+
+    /// What the tokenizer would make of a single word
+    pub fn token_key(&self, word: &str) -> String {
+        match self.mode {
+            Mode::Ascii => self.transliterator.transliterate(word).to_ascii_lowercase(),
+            Mode::Unicode => default_case_fold_str(word),
+        }
+    }
+
+    /// Whether documents need to go through language identification
+    pub fn detects_language(&self) -> bool {
+        self.auto_language || self.language_field.is_some() || self.language_confidence_field.is_some()
+    }
+
+    /// Whether the normalized text of each document has to be hashed
+    pub fn fingerprints_documents(&self) -> bool {
+        self.dedup != DedupMode::Off || self.hash_field.is_some() || self.minhash.is_some() || self.simhash_field.is_some()
+    }
+
+    /// Lexicon for a detected language, None means the default one should be used
+    pub fn detected_lexicon(&self, detection: Option<&Detection>) -> Option<&Lexicon> {
+        if !self.auto_language {
+            return None;
+        }
+        let language = detection.filter(|d| d.reliable).and_then(|d| d.algorithm)?;
+        self.detected_lexicons.iter().find(|lexicon| lexicon.language == language)
+    }
+}
+
+/// Every language with a Snowball stemmer
+const LANGUAGES: [Algorithm; 18] = [
+    Algorithm::Arabic, Algorithm::Danish, Algorithm::Dutch, Algorithm::English, Algorithm::Finnish,
+    Algorithm::French, Algorithm::German, Algorithm::Greek, Algorithm::Hungarian, Algorithm::Italian,
+    Algorithm::Norwegian, Algorithm::Portuguese, Algorithm::Romanian, Algorithm::Russian,
+    Algorithm::Spanish, Algorithm::Swedish, Algorithm::Tamil, Algorithm::Turkish,
+];
+
+/// Maps a language name (or ISO 639-1 code) to its Snowball stemmer
+pub fn parse_language(name: &str) -> Result<Algorithm> {
+    let algorithm = match name.to_ascii_lowercase().as_str() {
+        "arabic" | "ar" => Algorithm::Arabic,
+        "danish" | "da" => Algorithm::Danish,
+        "dutch" | "nl" => Algorithm::Dutch,
+        "english" | "en" => Algorithm::English,
+        "finnish" | "fi" => Algorithm::Finnish,
+        "french" | "fr" => Algorithm::French,
+        "german" | "de" => Algorithm::German,
+        "greek" | "el" => Algorithm::Greek,
+        "hungarian" | "hu" => Algorithm::Hungarian,
+        "italian" | "it" => Algorithm::Italian,
+        "norwegian" | "no" => Algorithm::Norwegian,
+        "portuguese" | "pt" => Algorithm::Portuguese,
+        "romanian" | "ro" => Algorithm::Romanian,
+        "russian" | "ru" => Algorithm::Russian,
+        "spanish" | "es" => Algorithm::Spanish,
+        "swedish" | "sv" => Algorithm::Swedish,
+        "tamil" | "ta" => Algorithm::Tamil,
+        "turkish" | "tr" => Algorithm::Turkish,
+        _ => return Err(invalid_input(format!("Unsupported language: {}", name))),
+    };
+    Ok(algorithm)
+}
+
+pub fn invalid_input(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
@@ -0,0 +1,126 @@
+//! protect.rs
+//!
+//! Provides the protected vocabulary: user-supplied terms ("C++", ".NET",
+//! "COVID-19", "US") that must survive stripping, the length filter,
+//! stopwords and stemming. Matches are found with Aho-Corasick on the raw
+//! text and emitted verbatim, or as their canonical form.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::io::Result;
+use aho_corasick::{AhoCorasick, MatchKind};
+use crate::entity::Piece;
+use crate::pipeline::invalid_input;
+
+pub struct ProtectedTokens {
+    matcher: AhoCorasick,
+    /// Output for each pattern, indexed by pattern id
+    outputs: Vec<String>,
+    /// Emit outputs as canonical words that still get stemmed, instead of verbatim
+    canonical: bool,
+}
+
+impl ProtectedTokens {
+    /// Builds the matcher from a term → output map, None when the map is empty
+    pub fn new(terms: &HashMap<String, String>, case_insensitive: bool) -> Result<Option<Self>> {
+        Self::build(terms, case_insensitive, false)
+    }
+
+    /// Same as `new`, but matches are emitted as canonical words (see `Piece::Canonical`)
+    pub fn canonical(terms: &HashMap<String, String>, case_insensitive: bool) -> Result<Option<Self>> {
+        Self::build(terms, case_insensitive, true)
+    }
+
+    fn build(terms: &HashMap<String, String>, case_insensitive: bool, canonical: bool) -> Result<Option<Self>> {
+        if terms.is_empty() {
+            return Ok(None);
+        }
+
+        let (patterns, outputs): (Vec<&str>, Vec<String>) = terms
+            .iter()
+            .filter(|(term, _)| !term.is_empty())
+            .map(|(term, output)| (term.as_str(), output.clone()))
+            .unzip();
+
+        // Every match is reported, so that a shorter term can be used when the longest one is
+        // not a whole word ("new york" in "new york cityscape")
+        let matcher = AhoCorasick::builder()
+            .match_kind(MatchKind::Standard)
+            .ascii_case_insensitive(case_insensitive)
+            .build(&patterns)
+            .map_err(|e| invalid_input(format!("Failed to build protected tokens: {}", e)))?;
+        Ok(Some(ProtectedTokens { matcher, outputs, canonical }))
+    }
+
+    /// Splits the text into regular text and protected tokens
+    /// Among the whole-word matches, the leftmost wins, then the longest
+    pub fn split<'a>(&'a self, text: &'a str) -> Vec<Piece<'a>> {
+        let mut matches: Vec<_> = self.matcher
+            .find_overlapping_iter(text)
+            .filter(|m| is_whole_word(text, m.start(), m.end()))
+            .collect();
+        matches.sort_unstable_by_key(|m| (m.start(), Reverse(m.end())));
+
+        let mut pieces = Vec::new();
+        let mut last = 0;
+        for m in matches {
+            // Overlaps a match already taken
+            if m.start() < last {
+                continue;
+            }
+            if m.start() > last {
+                pieces.push(Piece::Text(&text[last..m.start()]));
+            }
+            let output = &self.outputs[m.pattern().as_usize()];
+            pieces.push(if self.canonical { Piece::Canonical(output) } else { Piece::Token(output) });
+            last = m.end();
+        }
+        if last < text.len() {
+            pieces.push(Piece::Text(&text[last..]));
+        }
+        pieces
+    }
+}
+
+/// A match must not start or end in the middle of a word ("US" in "USA"),
+/// edges that are punctuation (".NET", "C++") can touch anything
+fn is_whole_word(text: &str, start: usize, end: usize) -> bool {
+    let matched = &text[start..end];
+    let starts_alnum = matched.chars().next().is_some_and(char::is_alphanumeric);
+    let ends_alnum = matched.chars().next_back().is_some_and(char::is_alphanumeric);
+
+    let before_ok = !starts_alnum || !text[..start].chars().next_back().is_some_and(char::is_alphanumeric);
+    let after_ok = !ends_alnum || !text[end..].chars().next().is_some_and(char::is_alphanumeric);
+    before_ok && after_ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(terms: &[&str], text: &str) -> Vec<String> {
+        let terms = terms.iter().map(|t| (t.to_string(), t.to_uppercase())).collect();
+        let protected = ProtectedTokens::new(&terms, true).unwrap().unwrap();
+        protected
+            .split(text)
+            .into_iter()
+            .map(|piece| match piece {
+                Piece::Token(token) => format!("[{}]", token),
+                Piece::Text(chunk) => chunk.to_string(),
+                Piece::Canonical(word) => word.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn prefers_longest_whole_word_match() {
+        assert_eq!(split(&["new york", "new york city"], "in new york city now"), ["in ", "[NEW YORK CITY]", " now"]);
+        assert_eq!(split(&["c", "c++"], "c++ and c"), ["[C++]", " and ", "[C]"]);
+    }
+
+    #[test]
+    fn falls_back_to_shorter_match() {
+        assert_eq!(split(&["new york", "new york city"], "new york cityscape"), ["[NEW YORK]", " cityscape"]);
+        assert_eq!(split(&["us", "usa"], "USAF and US"), ["USAF and ", "[US]"]);
+    }
+}
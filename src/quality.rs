@@ -0,0 +1,324 @@
+//! quality.rs
+//!
+//! Provides document quality heuristics in the spirit of Gopher and C4: word
+//! count and length, symbol and stopword ratios, punctuated lines and repeated
+//! n-grams. They are measured on the text as written (after markup stripping),
+//! before normalization removes the stopwords and punctuation they rely on.
+
+use std::collections::HashMap;
+use std::io::Result;
+use crate::filter::char_len;
+use crate::pipeline::invalid_input;
+use crate::set::StopwordSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Metric {
+    /// Whitespace-separated words
+    Words,
+    /// Mean length of the words, in characters
+    MeanWordLength,
+    /// "#" and ellipses per word
+    SymbolRatio,
+    /// Fraction of words in the stopword list of the document language
+    StopwordRatio,
+    /// Fraction of non-empty lines ending in terminal punctuation
+    PunctuatedLines,
+    /// Fraction of the word characters in the most frequent n-gram
+    TopNgram(usize),
+    /// Fraction of the word characters in n-grams occurring more than once
+    DuplicateNgram(usize),
+}
+
+impl Metric {
+    fn parse(name: &str) -> Option<Self> {
+        let ngram = |prefix: &str| {
+            name.strip_prefix(prefix)?
+                .strip_suffix("gram_fraction")?
+                .parse()
+                .ok()
+                .filter(|&n| n > 0)
+        };
+        match name {
+            "words" => Some(Metric::Words),
+            "mean_word_length" => Some(Metric::MeanWordLength),
+            "symbol_ratio" => Some(Metric::SymbolRatio),
+            "stopword_ratio" => Some(Metric::StopwordRatio),
+            "punctuated_lines" => Some(Metric::PunctuatedLines),
+            _ => ngram("top_").map(Metric::TopNgram).or_else(|| ngram("duplicate_").map(Metric::DuplicateNgram)),
+        }
+    }
+
+    /// Name of the score in the quality field
+    pub fn name(&self) -> String {
+        match self {
+            Metric::Words => "words".to_string(),
+            Metric::MeanWordLength => "mean_word_length".to_string(),
+            Metric::SymbolRatio => "symbol_ratio".to_string(),
+            Metric::StopwordRatio => "stopword_ratio".to_string(),
+            Metric::PunctuatedLines => "punctuated_lines".to_string(),
+            Metric::TopNgram(n) => format!("top_{}gram_fraction", n),
+            Metric::DuplicateNgram(n) => format!("duplicate_{}gram_fraction", n),
+        }
+    }
+}
+
+/// What happens to documents failing a filter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QualityAction {
+    /// They are left out of the output
+    Drop,
+    /// They are kept, the quality field tells them apart
+    Annotate,
+}
+
+impl QualityAction {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "drop" => Ok(QualityAction::Drop),
+            "annotate" => Ok(QualityAction::Annotate),
+            _ => Err(invalid_input(format!("Unknown quality action: {}", name))),
+        }
+    }
+}
+
+/// A `min_<metric>` or `max_<metric>` bound
+struct Threshold {
+    metric: Metric,
+    minimum: bool,
+    value: f64,
+}
+
+pub struct QualityFilters {
+    thresholds: Vec<Threshold>,
+    /// Every metric with a threshold, measured once each
+    metrics: Vec<Metric>,
+    pub action: QualityAction,
+    pub field: Option<String>,
+}
+
+/// Scores of a document, None when a metric is undefined (e.g. no stopword list)
+pub type Scores = Vec<(Metric, Option<f64>)>;
+
+impl QualityFilters {
+    /// None when no filter is configured
+    pub fn new(filters: &HashMap<String, f64>, action: &str, field: Option<String>) -> Result<Option<Self>> {
+        let action = QualityAction::parse(action)?;
+        if action == QualityAction::Annotate && field.is_none() {
+            return Err(invalid_input("The \"annotate\" quality action requires quality_field".to_string()));
+        }
+        if filters.is_empty() {
+            return Ok(None);
+        }
+
+        let mut thresholds = filters
+            .iter()
+            .map(|(name, &value)| {
+                let (minimum, metric) = match (name.strip_prefix("min_"), name.strip_prefix("max_")) {
+                    (Some(metric), _) => (true, metric),
+                    (_, Some(metric)) => (false, metric),
+                    _ => return Err(invalid_input(format!("Unknown quality filter: {}", name))),
+                };
+                let metric = Metric::parse(metric).ok_or_else(|| invalid_input(format!("Unknown quality filter: {}", name)))?;
+                Ok(Threshold { metric, minimum, value })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        thresholds.sort_by_key(|threshold| (threshold.metric, threshold.minimum));
+
+        let mut metrics: Vec<Metric> = thresholds.iter().map(|threshold| threshold.metric).collect();
+        metrics.dedup();
+        Ok(Some(QualityFilters { thresholds, metrics, action, field }))
+    }
+
+    pub fn measure(&self, text: &str, stopwords: Option<&StopwordSet>) -> Scores {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        self.metrics
+            .iter()
+            .map(|&metric| (metric, measure(metric, text, &words, stopwords)))
+            .collect()
+    }
+
+    /// Undefined scores pass every filter
+    pub fn passes(&self, scores: &Scores) -> bool {
+        self.thresholds.iter().all(|threshold| {
+            let score = scores.iter().find(|(metric, _)| *metric == threshold.metric).and_then(|(_, score)| *score);
+            score.is_none_or(|score| match threshold.minimum {
+                true => score >= threshold.value,
+                false => score <= threshold.value,
+            })
+        })
+    }
+}
+
+fn measure(metric: Metric, text: &str, words: &[&str], stopwords: Option<&StopwordSet>) -> Option<f64> {
+    let ratio = |count: usize, total: usize| (total > 0).then(|| count as f64 / total as f64);
+    match metric {
+        Metric::Words => Some(words.len() as f64),
+        Metric::MeanWordLength => ratio(words.iter().map(|word| char_len(word)).sum(), words.len()),
+        Metric::SymbolRatio => {
+            let symbols = text.matches('#').count() + text.matches("...").count() + text.matches('…').count();
+            ratio(symbols, words.len())
+        }
+        Metric::StopwordRatio => {
+            let stopwords = stopwords?;
+            let count = words
+                .iter()
+                .filter(|word| {
+                    let word = word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+                    stopwords.contains(word.as_bytes())
+                })
+                .count();
+            ratio(count, words.len())
+        }
+        Metric::PunctuatedLines => {
+            let lines: Vec<&str> = text.lines().map(str::trim_end).filter(|line| !line.is_empty()).collect();
+            let punctuated = lines
+                .iter()
+                .filter(|line| line.ends_with(['.', '!', '?', '"', '\'', '”', '’', '…']))
+                .count();
+            ratio(punctuated, lines.len())
+        }
+        Metric::TopNgram(n) => {
+            let counts = ngram_counts(words, n);
+            let top = counts.iter().max_by_key(|(ngram, count)| (**count, std::cmp::Reverse(**ngram))).map(|(ngram, _)| *ngram);
+            covered_fraction(words, n, |ngram| top == Some(ngram))
+        }
+        Metric::DuplicateNgram(n) => {
+            let counts = ngram_counts(words, n);
+            covered_fraction(words, n, |ngram| counts[ngram] > 1)
+        }
+    }
+}
+
+/// Fraction of the word characters covered by the n-grams matching `selected`,
+/// overlapping n-grams count their words once
+fn covered_fraction(words: &[&str], n: usize, selected: impl Fn(&[&str]) -> bool) -> Option<f64> {
+    let mut covered = vec![false; words.len()];
+    for (i, ngram) in words.windows(n).enumerate() {
+        if selected(ngram) {
+            covered[i..i + n].iter_mut().for_each(|covered| *covered = true);
+        }
+    }
+    let total: usize = words.iter().map(|word| char_len(word)).sum();
+    let count: usize = words.iter().zip(&covered).filter(|(_, covered)| **covered).map(|(word, _)| char_len(word)).sum();
+    (total > 0).then(|| count as f64 / total as f64)
+}
+
+fn ngram_counts<'a>(words: &'a [&'a str], n: usize) -> HashMap<&'a [&'a str], usize> {
+    let mut counts = HashMap::new();
+    for ngram in words.windows(n) {
+        *counts.entry(ngram).or_default() += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NormalizerConfig;
+    use crate::norm::{insert, normalize_owned_value};
+    use crate::pipeline::Pipeline;
+    use crate::set::STOPWORDS;
+    use simd_json::OwnedValue;
+    use simd_json::prelude::*;
+
+    // 11 words of 38 characters: 6 stopwords, a "#" and an ellipsis, 2 of 3 lines punctuated
+    const TEXT: &str = "The cat sat on the mat...\n#tags are here!\n\nno end";
+
+    fn score(metric: Metric, text: &str) -> Option<f64> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        measure(metric, text, &words, Some(&STOPWORDS))
+    }
+
+    fn filters(filters: &[(&str, f64)], action: &str) -> QualityFilters {
+        let filters = filters.iter().map(|&(name, value)| (name.to_string(), value)).collect();
+        QualityFilters::new(&filters, action, Some("quality".to_string())).unwrap().unwrap()
+    }
+
+    #[test]
+    fn measures_hand_computed_scores() {
+        assert_eq!(score(Metric::Words, TEXT), Some(11.0));
+        assert_eq!(score(Metric::MeanWordLength, TEXT), Some(38.0 / 11.0));
+        assert_eq!(score(Metric::SymbolRatio, TEXT), Some(2.0 / 11.0));
+        assert_eq!(score(Metric::StopwordRatio, TEXT), Some(6.0 / 11.0));
+        assert_eq!(score(Metric::PunctuatedLines, TEXT), Some(2.0 / 3.0));
+        // Words are counted in characters, not bytes
+        assert_eq!(score(Metric::MeanWordLength, "café été"), Some(3.5));
+    }
+
+    #[test]
+    fn ellipses_end_lines_and_count_as_symbols() {
+        assert_eq!(score(Metric::PunctuatedLines, "to be continued...\nwait for it…\nnothing"), Some(2.0 / 3.0));
+        assert_eq!(score(Metric::SymbolRatio, "to be continued... wait for it…"), Some(2.0 / 6.0));
+    }
+
+    #[test]
+    fn empty_texts_have_undefined_ratios() {
+        assert_eq!(score(Metric::Words, ""), Some(0.0));
+        assert_eq!(score(Metric::MeanWordLength, ""), None);
+        assert_eq!(score(Metric::PunctuatedLines, " \n"), None);
+        let words = ["the"];
+        assert_eq!(measure(Metric::StopwordRatio, "the", &words, None), None);
+    }
+
+    #[test]
+    fn measures_repeated_ngrams() {
+        // Bigrams: "x yy" twice, "yy x", "yy zzz", "zzz w" once each
+        let text = "x yy x yy zzz w";
+        assert_eq!(score(Metric::TopNgram(2), text), Some(6.0 / 10.0));
+        assert_eq!(score(Metric::DuplicateNgram(2), text), Some(6.0 / 10.0));
+        assert_eq!(score(Metric::DuplicateNgram(3), text), Some(0.0));
+        // Overlapping n-grams count their words once
+        assert_eq!(score(Metric::DuplicateNgram(1), "a a a b"), Some(3.0 / 4.0));
+    }
+
+    #[test]
+    fn thresholds_are_inclusive() {
+        let quality = filters(&[("min_words", 11.0), ("max_symbol_ratio", 2.0 / 11.0)], "drop");
+        assert!(quality.passes(&quality.measure(TEXT, Some(&STOPWORDS))));
+
+        let quality = filters(&[("min_words", 12.0)], "drop");
+        assert!(!quality.passes(&quality.measure(TEXT, Some(&STOPWORDS))));
+        let quality = filters(&[("max_symbol_ratio", 0.1)], "drop");
+        assert!(!quality.passes(&quality.measure(TEXT, Some(&STOPWORDS))));
+
+        // Undefined scores pass
+        let quality = filters(&[("min_stopword_ratio", 0.9)], "drop");
+        assert!(quality.passes(&quality.measure(TEXT, None)));
+    }
+
+    #[test]
+    fn rejects_unknown_filters() {
+        let new = |name: &str, action: &str, field: Option<&str>| {
+            QualityFilters::new(&HashMap::from([(name.to_string(), 1.0)]), action, field.map(str::to_string))
+        };
+        assert!(new("words", "drop", None).is_err());
+        assert!(new("min_letters", "drop", None).is_err());
+        assert!(new("max_top_0gram_fraction", "drop", None).is_err());
+        assert!(new("min_words", "annotate", None).is_err());
+        assert!(new("min_words", "skip", None).is_err());
+        assert!(new("max_duplicate_3gram_fraction", "annotate", Some("quality")).unwrap().is_some());
+    }
+
+    #[test]
+    fn drop_and_annotate_actions() {
+        let normalize = |action: &str| {
+            let config = NormalizerConfig {
+                quality_filters: HashMap::from([("min_words".to_string(), 3.0)]),
+                quality_action: action.to_string(),
+                quality_field: Some("quality".to_string()),
+                ..Default::default()
+            };
+            let pipeline = Pipeline::new(&config).unwrap();
+            ["too short", "long enough text"]
+                .map(|text| {
+                    let mut val = OwnedValue::object();
+                    insert(&mut val, "text", text).unwrap();
+                    let kept = normalize_owned_value(&mut val, "text", &pipeline, None).unwrap();
+                    let passes = val.get("quality").and_then(|quality| quality.get_bool("pass"));
+                    (kept, passes)
+                })
+        };
+        assert_eq!(normalize("drop"), [(false, Some(false)), (true, Some(true))]);
+        assert_eq!(normalize("annotate"), [(true, Some(false)), (true, Some(true))]);
+    }
+}
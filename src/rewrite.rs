@@ -0,0 +1,66 @@
+//! rewrite.rs
+//!
+//! Provides user-defined regex rewrite rules for one-off cleanups
+//! (boilerplate footers, tracking codes) that do not deserve a stage of their own.
+//! Rules are compiled once with the pipeline and applied in order, each one
+//! seeing the output of the previous one.
+
+use std::borrow::Cow;
+use std::io::Result;
+use regex::Regex;
+use crate::pipeline::invalid_input;
+
+/// Where the rules are applied
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RewriteStage {
+    /// On the document as received, before any other stage
+    Raw,
+    /// On the transliterated buffer, right before it is split into words
+    Ascii,
+}
+
+impl RewriteStage {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "raw" => Ok(RewriteStage::Raw),
+            "ascii" => Ok(RewriteStage::Ascii),
+            _ => Err(invalid_input(format!("Unknown rewrite stage: {}", name))),
+        }
+    }
+}
+
+pub struct Rewriter {
+    pub stage: RewriteStage,
+    /// Pattern and replacement (`$1`, `${name}` expand capture groups)
+    rules: Vec<(Regex, String)>,
+}
+
+impl Rewriter {
+    /// Compiles the rules in order, None when there are none
+    pub fn new(rules: &[(String, String)], stage: RewriteStage) -> Result<Option<Self>> {
+        if rules.is_empty() {
+            return Ok(None);
+        }
+
+        let rules = rules
+            .iter()
+            .map(|(pattern, replacement)| {
+                Regex::new(pattern)
+                    .map(|regex| (regex, replacement.clone()))
+                    .map_err(|e| invalid_input(format!("Invalid rewrite rule {:?}: {}", pattern, e)))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Some(Rewriter { stage, rules }))
+    }
+
+    /// Applies every rule, borrowing the input when none of them matches
+    pub fn apply<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for (regex, replacement) in &self.rules {
+            if let Cow::Owned(rewritten) = regex.replace_all(&text, replacement.as_str()) {
+                text = Cow::Owned(rewritten);
+            }
+        }
+        text
+    }
+}
@@ -0,0 +1,92 @@
+//! segment.rs
+//!
+//! Provides dictionary-based word segmentation for scripts written without
+//! spaces (Chinese, Japanese, Thai, Lao, Khmer, Myanmar).
+//! The dictionaries are the ICU4X ones compiled into the binary, so this
+//! works offline and needs no data files at runtime.
+
+use std::io::Result;
+use icu_segmenter::{WordSegmenter, WordSegmenterBorrowed};
+use icu_segmenter::options::WordBreakInvariantOptions;
+use crate::pipeline::invalid_input;
+
+/// When the dictionary segmenter is used instead of the regular tokenizer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Segmentation {
+    /// Never, complex scripts go through the regular tokenizer
+    Off,
+    /// Only for documents that contain a complex script
+    Auto,
+    /// For every document (slower, same output for space-delimited scripts)
+    Always,
+}
+
+impl Segmentation {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "off" => Ok(Segmentation::Off),
+            "auto" => Ok(Segmentation::Auto),
+            "always" => Ok(Segmentation::Always),
+            _ => Err(invalid_input(format!("Unknown segmentation: {}", name))),
+        }
+    }
+}
+
+pub struct Segmenter {
+    inner: WordSegmenterBorrowed<'static>,
+    policy: Segmentation,
+}
+
+impl Segmenter {
+    /// Returns None when segmentation is off so the pipeline can skip it entirely
+    pub fn new(policy: Segmentation) -> Option<Self> {
+        if policy == Segmentation::Off {
+            return None;
+        }
+        Some(Segmenter {
+            inner: WordSegmenter::new_dictionary(WordBreakInvariantOptions::default()),
+            policy,
+        })
+    }
+
+    /// Whether this document should be segmented
+    pub fn applies_to(&self, text: &str) -> bool {
+        match self.policy {
+            Segmentation::Off => false,
+            Segmentation::Auto => text.chars().any(is_complex_script),
+            Segmentation::Always => true,
+        }
+    }
+
+    /// Splits the text on word boundaries, yielding every segment
+    /// (words, whitespace and punctuation) along with whether it is word-like
+    pub fn segments<'a>(&self, text: &'a str) -> impl Iterator<Item = (&'a str, bool)> + 'a {
+        let mut prev = 0;
+        self.inner.segment_str(text).iter_with_word_type().filter_map(move |(i, word_type)| {
+            // The first boundary is always 0 and has no segment before it
+            let segment = &text[prev..i];
+            prev = i;
+            (!segment.is_empty()).then(|| (segment, word_type.is_word_like()))
+        })
+    }
+
+    /// Yields only the word-like segments (letters, ideographs and numbers)
+    pub fn words<'a>(&self, text: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.segments(text).filter_map(|(segment, is_word)| is_word.then_some(segment))
+    }
+}
+
+/// Scripts written without spaces between words
+pub fn is_complex_script(c: char) -> bool {
+    matches!(c as u32,
+        0x0E00..=0x0EFF     // Thai, Lao
+        | 0x1000..=0x109F   // Myanmar
+        | 0x1780..=0x17FF   // Khmer
+        | 0x3040..=0x30FF   // Hiragana, Katakana
+        | 0x3400..=0x4DBF   // CJK Extension A
+        | 0x4E00..=0x9FFF   // CJK Unified Ideographs
+        | 0xF900..=0xFAFF   // CJK Compatibility Ideographs
+        | 0xFF66..=0xFF9F   // Halfwidth Katakana
+        | 0x20000..=0x3134F // CJK Extensions B-G
+    )
+}
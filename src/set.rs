@@ -1,9 +1,20 @@
 use phf::phf_set;
+use rust_stemmers::Algorithm;
+
+pub type StopwordSet = phf::Set<&'static [u8]>;
+
+/// Returns the stopword list for a language, if we ship one
+pub fn stopwords(language: Algorithm) -> Option<&'static StopwordSet> {
+    match language {
+        Algorithm::English => Some(&STOPWORDS),
+        _ => None,
+    }
+}
 
 // Perfect hash function set for stopwords
 // Since it is known at compile time, we can squeeze some performance
 // (This set is based on the Spacy stopwords list)
-pub static STOPWORDS : phf::Set<&'static [u8]> = phf_set! {
+pub static STOPWORDS : StopwordSet = phf_set! {
     b"a", b"about", b"above", b"across", b"after", b"afterwards", b"again", b"against", b"all", b"almost",
     b"alone", b"along", b"already", b"also", b"although", b"always", b"am", b"among", b"amongst",
     b"amoungst", b"amount", b"an", b"and", b"another", b"any", b"anyhow", b"anyone", b"anything", b"anyway",
@@ -0,0 +1,676 @@
+//! simd.rs
+//!
+//! Provides the vectorized byte kernels of the ASCII tokenizer: masking
+//! non-alphanumeric bytes, the ASCII check, lowercasing and word boundaries.
+//! x86_64 picks AVX2 or SSE at runtime, aarch64 always has NEON, and every
+//! other target (or CPU) falls back to the scalar versions.
+
+const SPACE : u8 = b' ';
+
+/// Turns non-alphanumeric bytes into spaces, in place
+pub fn remove_non_alphanumeric(text: &mut [u8]) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            return unsafe { x86::remove_non_alphanumeric_avx2(text) };
+        } else if is_x86_feature_detected!("sse4.1") {
+            return unsafe { x86::remove_non_alphanumeric_sse2(text) };
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        return unsafe { neon::remove_non_alphanumeric_neon(text) };
+    }
+    #[allow(unreachable_code)]
+    remove_non_alphanumeric_scalar(text)
+}
+
+/// Whether the text is pure ASCII
+/// Most of the web text we process is, which lets us skip transliteration
+pub fn is_ascii(text: &[u8]) -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            return unsafe { x86::is_ascii_avx2(text) };
+        } else if is_x86_feature_detected!("sse2") {
+            return unsafe { x86::is_ascii_sse2(text) };
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        return unsafe { neon::is_ascii_neon(text) };
+    }
+    #[allow(unreachable_code)]
+    text.is_ascii()
+}
+
+/// Writes the ASCII lowercase of `text` to `out`, replacing its content
+pub fn lowercase_into(text: &[u8], out: &mut Vec<u8>) {
+    out.clear();
+    out.resize(text.len(), 0);
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            return unsafe { x86::lowercase_avx2(text, out) };
+        } else if is_x86_feature_detected!("sse2") {
+            return unsafe { x86::lowercase_sse2(text, out) };
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        return unsafe { neon::lowercase_neon(text, out) };
+    }
+    #[allow(unreachable_code)]
+    lowercase_scalar(text, out)
+}
+
+/// Calls `f` with the start and end of every run of non-space bytes, in order
+///
+/// Spaces are found 64 bytes at a time as a bitmask, word starts and ends are
+/// then the 0 → 1 and 1 → 0 transitions of the mask, visited with trailing_zeros
+/// instead of looking at every byte.
+pub fn for_each_word(text: &[u8], mut f: impl FnMut(usize, usize)) {
+    let space_mask = space_mask_fn();
+
+    let mut start = 0;
+    // Whether the byte right before the current block belongs to a word
+    let mut carry = 0u64;
+    let mut base = 0;
+    while base < text.len() {
+        let block = &text[base..text.len().min(base + 64)];
+        let spaces = match block.len() {
+            64 => space_mask(block),
+            // Bytes past the end of the text count as spaces
+            n => space_mask_scalar(block) | (u64::MAX << n),
+        };
+
+        let words = !spaces;
+        let previous = (words << 1) | carry;
+        let mut transitions = (words & !previous) | (!words & previous);
+        while transitions != 0 {
+            let i = transitions.trailing_zeros() as usize;
+            if words & (1 << i) != 0 {
+                start = base + i;
+            } else {
+                f(start, base + i);
+            }
+            transitions &= transitions - 1;
+        }
+
+        carry = words >> 63;
+        base += 64;
+    }
+    if carry != 0 {
+        f(start, text.len());
+    }
+}
+
+/// Picks the bitmask kernel once per call of `for_each_word`
+fn space_mask_fn() -> fn(&[u8]) -> u64 {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            return |block| unsafe { x86::space_mask_avx2(block) };
+        } else if is_x86_feature_detected!("sse2") {
+            return |block| unsafe { x86::space_mask_sse2(block) };
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        return |block| unsafe { neon::space_mask_neon(block) };
+    }
+    #[allow(unreachable_code)]
+    space_mask_scalar
+}
+
+fn remove_non_alphanumeric_scalar(text: &mut [u8]) {
+    // Base logic (1 byte at a time)
+    for byte in text.iter_mut() {
+        if !byte.is_ascii_alphanumeric() {
+            *byte = SPACE;
+        }
+    }
+}
+
+fn lowercase_scalar(text: &[u8], out: &mut [u8]) {
+    for (out, byte) in out.iter_mut().zip(text) {
+        *out = byte.to_ascii_lowercase();
+    }
+}
+
+/// Bit i is set when block[i] is a space, for blocks of up to 64 bytes
+fn space_mask_scalar(block: &[u8]) -> u64 {
+    block
+        .iter()
+        .enumerate()
+        .filter(|&(_, &b)| b == SPACE)
+        .fold(0, |mask, (i, _)| mask | (1 << i))
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+    use super::{lowercase_scalar, remove_non_alphanumeric_scalar, SPACE};
+
+    #[target_feature(enable = "sse4.1")]
+    pub unsafe fn remove_non_alphanumeric_sse2(text: &mut [u8]) {
+        // SSE2 logic (16 bytes at a time)
+
+        let ptr : *mut u8 = text.as_mut_ptr();
+        let len: usize = text.len();
+        let mut i = 0;
+
+        unsafe {
+            let space = _mm_set1_epi8(SPACE as i8);
+
+            while i + 16 <= len {
+                // Load 16 bytes from the input
+                let chunk = _mm_loadu_si128(ptr.add(i) as *const __m128i);
+
+                let is_digit = {
+                    let digit_min = _mm_set1_epi8(b'0' as i8 - 1);
+                    let digit_max = _mm_set1_epi8(b'9' as i8 + 1);
+                    let ge_digit_min = _mm_cmpgt_epi8(chunk, digit_min);
+                    let le_digit_max = _mm_cmplt_epi8(chunk, digit_max);
+                    _mm_and_si128(ge_digit_min, le_digit_max)
+                };
+
+                let is_upper = {
+                    let upper_min = _mm_set1_epi8(b'A' as i8 - 1);
+                    let upper_max = _mm_set1_epi8(b'Z' as i8 + 1);
+                    let ge_upper_min = _mm_cmpgt_epi8(chunk, upper_min);
+                    let le_upper_max = _mm_cmplt_epi8(chunk, upper_max);
+                    _mm_and_si128(ge_upper_min, le_upper_max)
+                };
+
+                let is_lower = {
+                    let lower_min = _mm_set1_epi8(b'a' as i8 - 1);
+                    let lower_max = _mm_set1_epi8(b'z' as i8 + 1);
+                    let ge_lower_min = _mm_cmpgt_epi8(chunk, lower_min);
+                    let le_lower_max = _mm_cmplt_epi8(chunk, lower_max);
+                    _mm_and_si128(ge_lower_min, le_lower_max)
+                };
+
+                // Combine the masks
+                let is_alphanumeric = _mm_or_si128(_mm_or_si128(is_digit, is_upper), is_lower);
+
+                // Create a mask for non-alphanumeric characters
+                let non_alphanumeric_mask = _mm_cmpeq_epi8(is_alphanumeric, _mm_setzero_si128());
+
+                let result = _mm_blendv_epi8(chunk, space, non_alphanumeric_mask);
+                // Store the result back to the output
+                _mm_storeu_si128(ptr.add(i) as *mut __m128i, result);
+
+                i += 16;
+            }
+        }
+
+        // Process remaining bytes
+        remove_non_alphanumeric_scalar(&mut text[i..]);
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn remove_non_alphanumeric_avx2(text: &mut [u8]) {
+        // AVX2 logic (32 bytes at a time)
+        let ptr : *mut u8 = text.as_mut_ptr();
+        let len: usize = text.len();
+        let mut i = 0;
+
+        unsafe {
+            let space = _mm256_set1_epi8(SPACE as i8);
+            while i + 32 <= len {
+                // Load 32 bytes from the input
+                let chunk = _mm256_loadu_si256(ptr.add(i) as *const __m256i);
+
+                let is_digit = {
+                    let digit_min = _mm256_set1_epi8(b'0' as i8 - 1); // We subtract 1 to include '0' since simd does not have less than or equal
+                    let digit_max = _mm256_set1_epi8(b'9' as i8 + 1); // Same here, we add 1 to include '9'
+                    let ge_digit_min = _mm256_cmpgt_epi8(chunk, digit_min); // chunk > '0' - 1
+                    let le_digit_max = _mm256_cmpgt_epi8(digit_max, chunk); // chunk < '9' + 1
+                    _mm256_and_si256(ge_digit_min, le_digit_max)
+                };
+
+                let is_upper = {
+                    let upper_min = _mm256_set1_epi8(b'A' as i8 - 1); // And so on...
+                    let upper_max = _mm256_set1_epi8(b'Z' as i8 + 1);
+                    let ge_upper_min = _mm256_cmpgt_epi8(chunk, upper_min); // chunk > 'A' - 1
+                    let le_upper_max = _mm256_cmpgt_epi8(upper_max, chunk); // chunk < 'Z' + 1
+                    _mm256_and_si256(ge_upper_min, le_upper_max)
+                };
+
+                let is_lower = {
+                    let lower_min = _mm256_set1_epi8(b'a' as i8 - 1);
+                    let lower_max = _mm256_set1_epi8(b'z' as i8 + 1);
+                    let ge_lower_min = _mm256_cmpgt_epi8(chunk, lower_min);
+                    let le_lower_max = _mm256_cmpgt_epi8(lower_max, chunk);
+                    _mm256_and_si256(ge_lower_min, le_lower_max)
+                };
+
+                // Combine the masks
+                let is_alphanumeric = _mm256_or_si256(_mm256_or_si256(is_digit, is_upper), is_lower);
+
+                // Create a mask for non-alphanumeric characters
+                let non_alphanumeric_mask = _mm256_cmpeq_epi8(is_alphanumeric, _mm256_setzero_si256());
+
+                let result = _mm256_blendv_epi8(chunk, space, non_alphanumeric_mask);
+                // Store the result back to the output
+                _mm256_storeu_si256(ptr.add(i) as *mut __m256i, result);
+
+                i += 32;
+            }
+
+            // The SSE version takes care of the last 16 bytes and the remainder
+            remove_non_alphanumeric_sse2(&mut text[i..]);
+        }
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn is_ascii_sse2(text: &[u8]) -> bool {
+        // SSE2 logic (16 bytes at a time): non-ASCII bytes have their high bit set,
+        // which is what movemask collects
+        let ptr : *const u8 = text.as_ptr();
+        let len: usize = text.len();
+        let mut i = 0;
+
+        unsafe {
+            while i + 16 <= len {
+                let chunk = _mm_loadu_si128(ptr.add(i) as *const __m128i);
+                if _mm_movemask_epi8(chunk) != 0 {
+                    return false;
+                }
+                i += 16;
+            }
+        }
+
+        text[i..].is_ascii()
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn is_ascii_avx2(text: &[u8]) -> bool {
+        // AVX2 logic (64 bytes at a time, two loads OR'ed before checking the high bits)
+        let ptr : *const u8 = text.as_ptr();
+        let len: usize = text.len();
+        let mut i = 0;
+
+        unsafe {
+            while i + 64 <= len {
+                let low = _mm256_loadu_si256(ptr.add(i) as *const __m256i);
+                let high = _mm256_loadu_si256(ptr.add(i + 32) as *const __m256i);
+                if _mm256_movemask_epi8(_mm256_or_si256(low, high)) != 0 {
+                    return false;
+                }
+                i += 64;
+            }
+            is_ascii_sse2(&text[i..])
+        }
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn lowercase_sse2(text: &[u8], out: &mut [u8]) {
+        // SSE2 logic (16 bytes at a time): 'A'..='Z' get their 0x20 bit set
+        let len: usize = text.len().min(out.len());
+        let mut i = 0;
+
+        unsafe {
+            let upper_min = _mm_set1_epi8(b'A' as i8 - 1);
+            let upper_max = _mm_set1_epi8(b'Z' as i8 + 1);
+            let case_bit = _mm_set1_epi8(0x20);
+            while i + 16 <= len {
+                let chunk = _mm_loadu_si128(text.as_ptr().add(i) as *const __m128i);
+                let is_upper = _mm_and_si128(_mm_cmpgt_epi8(chunk, upper_min), _mm_cmplt_epi8(chunk, upper_max));
+                let result = _mm_or_si128(chunk, _mm_and_si128(is_upper, case_bit));
+                _mm_storeu_si128(out.as_mut_ptr().add(i) as *mut __m128i, result);
+                i += 16;
+            }
+        }
+
+        lowercase_scalar(&text[i..], &mut out[i..]);
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn lowercase_avx2(text: &[u8], out: &mut [u8]) {
+        // AVX2 logic (32 bytes at a time)
+        let len: usize = text.len().min(out.len());
+        let mut i = 0;
+
+        unsafe {
+            let upper_min = _mm256_set1_epi8(b'A' as i8 - 1);
+            let upper_max = _mm256_set1_epi8(b'Z' as i8 + 1);
+            let case_bit = _mm256_set1_epi8(0x20);
+            while i + 32 <= len {
+                let chunk = _mm256_loadu_si256(text.as_ptr().add(i) as *const __m256i);
+                let is_upper = _mm256_and_si256(_mm256_cmpgt_epi8(chunk, upper_min), _mm256_cmpgt_epi8(upper_max, chunk));
+                let result = _mm256_or_si256(chunk, _mm256_and_si256(is_upper, case_bit));
+                _mm256_storeu_si256(out.as_mut_ptr().add(i) as *mut __m256i, result);
+                i += 32;
+            }
+
+            lowercase_sse2(&text[i..], &mut out[i..]);
+        }
+    }
+
+    /// Expects a block of exactly 64 bytes
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn space_mask_sse2(block: &[u8]) -> u64 {
+        let mut mask = 0u64;
+        unsafe {
+            let space = _mm_set1_epi8(SPACE as i8);
+            for (n, chunk) in block.chunks_exact(16).enumerate() {
+                let chunk = _mm_loadu_si128(chunk.as_ptr() as *const __m128i);
+                let bits = _mm_movemask_epi8(_mm_cmpeq_epi8(chunk, space)) as u16;
+                mask |= (bits as u64) << (n * 16);
+            }
+        }
+        mask
+    }
+
+    /// Expects a block of exactly 64 bytes
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn space_mask_avx2(block: &[u8]) -> u64 {
+        unsafe {
+            let space = _mm256_set1_epi8(SPACE as i8);
+            let low = _mm256_loadu_si256(block.as_ptr() as *const __m256i);
+            let high = _mm256_loadu_si256(block.as_ptr().add(32) as *const __m256i);
+            let low = _mm256_movemask_epi8(_mm256_cmpeq_epi8(low, space)) as u32;
+            let high = _mm256_movemask_epi8(_mm256_cmpeq_epi8(high, space)) as u32;
+            (low as u64) | ((high as u64) << 32)
+        }
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use std::arch::aarch64::*;
+    use super::{lowercase_scalar, remove_non_alphanumeric_scalar, SPACE};
+
+    /// 0xFF where the byte is in `min..=max`
+    #[inline]
+    unsafe fn in_range(chunk: uint8x16_t, min: u8, max: u8) -> uint8x16_t {
+        unsafe { vandq_u8(vcgeq_u8(chunk, vdupq_n_u8(min)), vcleq_u8(chunk, vdupq_n_u8(max))) }
+    }
+
+    pub unsafe fn remove_non_alphanumeric_neon(text: &mut [u8]) {
+        // NEON logic (16 bytes at a time), unsigned compares so non-ASCII bytes are never in range
+        let ptr : *mut u8 = text.as_mut_ptr();
+        let len: usize = text.len();
+        let mut i = 0;
+
+        unsafe {
+            let space = vdupq_n_u8(SPACE);
+            while i + 16 <= len {
+                let chunk = vld1q_u8(ptr.add(i));
+                let is_alphanumeric = vorrq_u8(
+                    in_range(chunk, b'0', b'9'),
+                    vorrq_u8(in_range(chunk, b'A', b'Z'), in_range(chunk, b'a', b'z')),
+                );
+                // Keep alphanumeric bytes, take the space elsewhere
+                vst1q_u8(ptr.add(i), vbslq_u8(is_alphanumeric, chunk, space));
+                i += 16;
+            }
+        }
+
+        remove_non_alphanumeric_scalar(&mut text[i..]);
+    }
+
+    pub unsafe fn is_ascii_neon(text: &[u8]) -> bool {
+        let len: usize = text.len();
+        let mut i = 0;
+
+        unsafe {
+            while i + 16 <= len {
+                if vmaxvq_u8(vld1q_u8(text.as_ptr().add(i))) >= 0x80 {
+                    return false;
+                }
+                i += 16;
+            }
+        }
+
+        text[i..].is_ascii()
+    }
+
+    pub unsafe fn lowercase_neon(text: &[u8], out: &mut [u8]) {
+        let len: usize = text.len().min(out.len());
+        let mut i = 0;
+
+        unsafe {
+            let case_bit = vdupq_n_u8(0x20);
+            while i + 16 <= len {
+                let chunk = vld1q_u8(text.as_ptr().add(i));
+                let result = vorrq_u8(chunk, vandq_u8(in_range(chunk, b'A', b'Z'), case_bit));
+                vst1q_u8(out.as_mut_ptr().add(i), result);
+                i += 16;
+            }
+        }
+
+        lowercase_scalar(&text[i..], &mut out[i..]);
+    }
+
+    /// Expects a block of exactly 64 bytes
+    pub unsafe fn space_mask_neon(block: &[u8]) -> u64 {
+        // NEON has no movemask: each lane keeps its own bit, and the 8 lanes
+        // of each half are summed horizontally into a byte
+        const BITS: [u8; 16] = [1, 2, 4, 8, 16, 32, 64, 128, 1, 2, 4, 8, 16, 32, 64, 128];
+        let mut mask = 0u64;
+        unsafe {
+            let space = vdupq_n_u8(SPACE);
+            let bits = vld1q_u8(BITS.as_ptr());
+            for (n, chunk) in block.chunks_exact(16).enumerate() {
+                let lanes = vandq_u8(vceqq_u8(vld1q_u8(chunk.as_ptr()), space), bits);
+                let low = vaddv_u8(vget_low_u8(lanes)) as u64;
+                let high = vaddv_u8(vget_high_u8(lanes)) as u64;
+                mask |= (low | (high << 8)) << (n * 16);
+            }
+        }
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A kernel and its name, for the assertion messages
+    type RemoveKernel = (&'static str, fn(&mut [u8]));
+    type IsAsciiKernel = (&'static str, fn(&[u8]) -> bool);
+    type LowercaseKernel = (&'static str, fn(&[u8], &mut [u8]));
+    type SpaceMaskKernel = (&'static str, fn(&[u8]) -> u64);
+
+    /// Every remove_non_alphanumeric kernel this CPU can run
+    fn remove_kernels() -> Vec<RemoveKernel> {
+        let mut kernels: Vec<RemoveKernel> = vec![("dispatch", remove_non_alphanumeric)];
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("sse4.1") {
+                kernels.push(("sse", |text| unsafe { x86::remove_non_alphanumeric_sse2(text) }));
+            }
+            if is_x86_feature_detected!("avx2") {
+                kernels.push(("avx2", |text| unsafe { x86::remove_non_alphanumeric_avx2(text) }));
+            }
+        }
+        #[cfg(target_arch = "aarch64")]
+        kernels.push(("neon", |text| unsafe { neon::remove_non_alphanumeric_neon(text) }));
+        kernels
+    }
+
+    fn is_ascii_kernels() -> Vec<IsAsciiKernel> {
+        let mut kernels: Vec<IsAsciiKernel> = vec![("dispatch", is_ascii)];
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("sse2") {
+                kernels.push(("sse2", |text| unsafe { x86::is_ascii_sse2(text) }));
+            }
+            if is_x86_feature_detected!("avx2") {
+                kernels.push(("avx2", |text| unsafe { x86::is_ascii_avx2(text) }));
+            }
+        }
+        #[cfg(target_arch = "aarch64")]
+        kernels.push(("neon", |text| unsafe { neon::is_ascii_neon(text) }));
+        kernels
+    }
+
+    fn lowercase_kernels() -> Vec<LowercaseKernel> {
+        let mut kernels: Vec<LowercaseKernel> = Vec::new();
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("sse2") {
+                kernels.push(("sse2", |text, out| unsafe { x86::lowercase_sse2(text, out) }));
+            }
+            if is_x86_feature_detected!("avx2") {
+                kernels.push(("avx2", |text, out| unsafe { x86::lowercase_avx2(text, out) }));
+            }
+        }
+        #[cfg(target_arch = "aarch64")]
+        kernels.push(("neon", |text, out| unsafe { neon::lowercase_neon(text, out) }));
+        kernels
+    }
+
+    fn space_mask_kernels() -> Vec<SpaceMaskKernel> {
+        let mut kernels: Vec<SpaceMaskKernel> = vec![("dispatch", space_mask_fn())];
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("sse2") {
+                kernels.push(("sse2", |block| unsafe { x86::space_mask_sse2(block) }));
+            }
+            if is_x86_feature_detected!("avx2") {
+                kernels.push(("avx2", |block| unsafe { x86::space_mask_avx2(block) }));
+            }
+        }
+        #[cfg(target_arch = "aarch64")]
+        kernels.push(("neon", |block| unsafe { neon::space_mask_neon(block) }));
+        kernels
+    }
+
+    /// Deterministic bytes covering the whole 0..=255 range (xorshift)
+    fn random_bytes(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    /// Words of `word_len` letters separated by single spaces, starting after `offset` spaces,
+    /// so that words cross the 16, 32 and 64-byte boundaries at every position
+    fn words_text(len: usize, word_len: usize, offset: usize) -> Vec<u8> {
+        (0..len)
+            .map(|i| match i.checked_sub(offset) {
+                Some(i) if i % (word_len + 1) != word_len => b'a' + (i % 26) as u8,
+                _ => SPACE,
+            })
+            .collect()
+    }
+
+    /// Every test input of `len` bytes: random bytes, random ASCII, and words of many lengths
+    fn inputs(len: usize) -> Vec<Vec<u8>> {
+        let mut inputs = vec![
+            random_bytes(len, len as u64),
+            random_bytes(len, len as u64 + 1000).into_iter().map(|b| b & 0x7F).collect(),
+            // Mostly spaces, the worst case for the word boundaries
+            random_bytes(len, len as u64 + 2000).into_iter().map(|b| if b < 200 { SPACE } else { b'x' }).collect(),
+        ];
+        for word_len in [1, 2, 3, 7, 15, 16, 17, 31, 32, 33, 63, 64, 65, 100] {
+            for offset in [0, 1, 5] {
+                inputs.push(words_text(len, word_len, offset));
+            }
+        }
+        inputs
+    }
+
+    fn words_scalar(text: &[u8]) -> Vec<(usize, usize)> {
+        let mut words = Vec::new();
+        let mut start = None;
+        for (i, &b) in text.iter().chain([SPACE].iter()).enumerate() {
+            match (start, b == SPACE) {
+                (None, false) => start = Some(i),
+                (Some(s), true) => {
+                    words.push((s, i));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        words
+    }
+
+    #[test]
+    fn remove_non_alphanumeric_matches_scalar() {
+        for len in 0..=200 {
+            for input in inputs(len) {
+                let mut expected = input.clone();
+                remove_non_alphanumeric_scalar(&mut expected);
+                for (name, kernel) in remove_kernels() {
+                    let mut actual = input.clone();
+                    kernel(&mut actual);
+                    assert_eq!(actual, expected, "{} kernel, {} bytes", name, len);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn is_ascii_matches_scalar() {
+        for len in 0..=200 {
+            let ascii = words_text(len, 5, 0);
+            // A single non-ASCII byte at every position, including the tail after the last full vector
+            let mut texts = vec![ascii.clone()];
+            for i in 0..len {
+                let mut text = ascii.clone();
+                text[i] = 0x80 | (i as u8);
+                texts.push(text);
+            }
+            for text in texts {
+                for (name, kernel) in is_ascii_kernels() {
+                    assert_eq!(kernel(&text), text.is_ascii(), "{} kernel, {} bytes", name, len);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lowercase_matches_scalar() {
+        for len in 0..=200 {
+            for input in inputs(len) {
+                let mut expected = vec![0; len];
+                lowercase_scalar(&input, &mut expected);
+
+                let mut dispatched = Vec::new();
+                lowercase_into(&input, &mut dispatched);
+                assert_eq!(dispatched, expected, "dispatch, {} bytes", len);
+
+                for (name, kernel) in lowercase_kernels() {
+                    let mut actual = vec![0; len];
+                    kernel(&input, &mut actual);
+                    assert_eq!(actual, expected, "{} kernel, {} bytes", name, len);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn space_mask_matches_scalar() {
+        for input in inputs(64 + 200) {
+            for offset in 0..200 {
+                let block = &input[offset..offset + 64];
+                let expected = space_mask_scalar(block);
+                for (name, kernel) in space_mask_kernels() {
+                    assert_eq!(kernel(block), expected, "{} kernel, offset {}", name, offset);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn for_each_word_matches_scalar() {
+        for len in 0..=200 {
+            for input in inputs(len) {
+                let mut words = Vec::new();
+                for_each_word(&input, |start, end| words.push((start, end)));
+                assert_eq!(words, words_scalar(&input), "{} bytes: {:?}", len, String::from_utf8_lossy(&input));
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! simhash.rs
+//!
+//! Provides 64-bit SimHash fingerprints of normalized texts, and the search
+//! for pairs of fingerprints within a Hamming distance.
+//! The search splits fingerprints into `max_distance + 1` blocks: two fingerprints
+//! that close agree on at least one block, so only documents sharing a block
+//! value are compared.
+
+use std::collections::HashMap;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// SimHash of a normalized text (words separated by single spaces), every occurrence counts
+pub fn simhash(text: &str) -> u64 {
+    let mut weights = [0i32; 64];
+    for word in text.split(' ') {
+        let hash = xxh3_64(word.as_bytes());
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            *weight += if hash >> bit & 1 == 1 { 1 } else { -1 };
+        }
+    }
+    weights
+        .iter()
+        .enumerate()
+        .filter(|&(_, &weight)| weight > 0)
+        .fold(0, |simhash, (bit, _)| simhash | (1 << bit))
+}
+
+/// Pairs (i, j, distance) with i < j of fingerprints at most `max_distance` bits apart,
+/// sorted by i then j. `max_distance` must be below 64.
+///
+/// Documents sharing a block value are compared pairwise, so very common
+/// fingerprints (e.g. thousands of copies of a boilerplate page) are slow.
+pub fn find_pairs(simhashes: &[u64], max_distance: u32) -> Vec<(usize, usize, u32)> {
+    let masks = block_masks(max_distance as usize + 1);
+
+    let mut pairs = Vec::new();
+    for (block, &mask) in masks.iter().enumerate() {
+        let mut groups: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (i, &simhash) in simhashes.iter().enumerate() {
+            groups.entry(simhash & mask).or_default().push(i);
+        }
+
+        for group in groups.values().filter(|group| group.len() > 1) {
+            for (n, &i) in group.iter().enumerate() {
+                for &j in &group[n + 1..] {
+                    let (a, b) = (simhashes[i], simhashes[j]);
+                    let distance = (a ^ b).count_ones();
+                    // Pairs agreeing on an earlier block were found with it already
+                    if distance <= max_distance && masks[..block].iter().all(|&m| a & m != b & m) {
+                        pairs.push((i, j, distance));
+                    }
+                }
+            }
+        }
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+/// Splits the 64 bits into `blocks` contiguous masks of (nearly) equal size
+fn block_masks(blocks: usize) -> Vec<u64> {
+    (0..blocks)
+        .map(|block| {
+            let start = block * 64 / blocks;
+            let end = (block + 1) * 64 / blocks;
+            match end - start {
+                64 => u64::MAX,
+                width => ((1u64 << width) - 1) << start,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fingerprints around a few centers, so that every distance shows up
+    fn fingerprints(seed: u64) -> Vec<u64> {
+        let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let centers: Vec<u64> = (0..4).map(|_| next()).collect();
+        (0..300)
+            .map(|n| {
+                let mut simhash = centers[n % centers.len()];
+                // Flip between 0 and 63 random bits, some of them twice
+                for _ in 0..next() % 64 {
+                    simhash ^= 1 << (next() % 64);
+                }
+                simhash
+            })
+            .chain([0, u64::MAX, 0])
+            .collect()
+    }
+
+    fn brute_force(simhashes: &[u64], max_distance: u32) -> Vec<(usize, usize, u32)> {
+        let mut pairs = Vec::new();
+        for i in 0..simhashes.len() {
+            for j in i + 1..simhashes.len() {
+                let distance = (simhashes[i] ^ simhashes[j]).count_ones();
+                if distance <= max_distance {
+                    pairs.push((i, j, distance));
+                }
+            }
+        }
+        pairs
+    }
+
+    #[test]
+    fn find_pairs_matches_brute_force() {
+        for seed in 1..4 {
+            let simhashes = fingerprints(seed);
+            for max_distance in [0, 1, 3, 7, 16, 31, 32, 63] {
+                assert_eq!(
+                    find_pairs(&simhashes, max_distance),
+                    brute_force(&simhashes, max_distance),
+                    "seed {}, distance {}", seed, max_distance
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn block_masks_cover_every_bit_once() {
+        for blocks in 1..=64 {
+            let masks = block_masks(blocks);
+            assert_eq!(masks.len(), blocks);
+            assert_eq!(masks.iter().fold(0, |all, &mask| all | mask), u64::MAX);
+            assert_eq!(masks.iter().map(|mask| mask.count_ones()).sum::<u32>(), 64);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! stem.rs
+//!
+//! Provides the stemmers selectable in the config.
+//! Snowball (Porter2 for English) is the default and the only one covering
+//! every language. The others are English only and trade recall for readable
+//! output: "punctuation" stays "punctuation" with the S-stemmer and the
+//! lemmatizer, where Porter2 gives "punctuat".
+
+mod irregular;
+mod krovetz;
+mod lancaster;
+mod lemma;
+mod lovins;
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Result;
+use std::num::NonZeroUsize;
+use lru::LruCache;
+use rust_stemmers::{Algorithm, Stemmer};
+use crate::pipeline::invalid_input;
+
+/// Which stemmer the config asks for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StemmerKind {
+    Snowball,
+    None,
+    /// Harman's S-stemmer, plurals only
+    S,
+    Lovins,
+    Lancaster,
+    Krovetz,
+    Lemmatizer,
+}
+
+impl StemmerKind {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "snowball" | "porter2" => Ok(StemmerKind::Snowball),
+            "none" => Ok(StemmerKind::None),
+            "s" => Ok(StemmerKind::S),
+            "lovins" => Ok(StemmerKind::Lovins),
+            "lancaster" => Ok(StemmerKind::Lancaster),
+            "krovetz" => Ok(StemmerKind::Krovetz),
+            "lemmatizer" => Ok(StemmerKind::Lemmatizer),
+            _ => Err(invalid_input(format!("Unknown stemmer: {}", name))),
+        }
+    }
+
+    pub fn is_english_only(self) -> bool {
+        !matches!(self, StemmerKind::Snowball | StemmerKind::None)
+    }
+}
+
+pub enum WordStemmer {
+    None,
+    Snowball(Stemmer),
+    S,
+    Lovins,
+    Lancaster,
+    Krovetz,
+    Lemmatizer,
+}
+
+impl WordStemmer {
+    /// English-only stemmers fall back to Snowball for other languages
+    /// (e.g. documents detected as French with auto_language)
+    pub fn new(kind: StemmerKind, language: Algorithm) -> Self {
+        if kind.is_english_only() && language != Algorithm::English {
+            return WordStemmer::Snowball(Stemmer::create(language));
+        }
+        match kind {
+            StemmerKind::Snowball => WordStemmer::Snowball(Stemmer::create(language)),
+            StemmerKind::None => WordStemmer::None,
+            StemmerKind::S => WordStemmer::S,
+            StemmerKind::Lovins => WordStemmer::Lovins,
+            StemmerKind::Lancaster => WordStemmer::Lancaster,
+            StemmerKind::Krovetz => WordStemmer::Krovetz,
+            StemmerKind::Lemmatizer => WordStemmer::Lemmatizer,
+        }
+    }
+
+    /// Stems a lowercased word, borrowing it when it is left unchanged
+    pub fn stem<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        // The English stemmers work on bytes, anything else is not English anyway
+        if !matches!(self, WordStemmer::Snowball(_)) && !word.is_ascii() {
+            return Cow::Borrowed(word);
+        }
+        match self {
+            WordStemmer::None => Cow::Borrowed(word),
+            WordStemmer::Snowball(stemmer) => stemmer.stem(word),
+            WordStemmer::S => s_stem(word),
+            WordStemmer::Lovins => lovins::stem(word),
+            WordStemmer::Lancaster => lancaster::stem(word),
+            WordStemmer::Krovetz => krovetz::stem(word),
+            WordStemmer::Lemmatizer => lemma::lemmatize(word),
+        }
+    }
+}
+
+thread_local! {
+    /// Stem caches of the current thread, one per lexicon
+    static STEM_CACHES: RefCell<HashMap<usize, LruCache<String, String>>> = RefCell::new(HashMap::new());
+}
+
+/// Caches of lexicons that are gone are never reclaimed, so a thread keeps at most this many
+const MAX_CACHES: usize = 64;
+
+/// Runs `f` on the stem of a word, looking it up in the bounded per-thread cache of the lexicon `id` first
+/// Frequent words are stemmed once per thread instead of once per occurrence
+pub fn with_cached_stem<R>(stemmer: &WordStemmer, id: usize, capacity: NonZeroUsize, word: &str, f: impl FnOnce(&str) -> R) -> R {
+    STEM_CACHES.with_borrow_mut(|caches| {
+        if caches.len() >= MAX_CACHES && !caches.contains_key(&id) {
+            caches.clear();
+        }
+        let cache = caches.entry(id).or_insert_with(|| LruCache::new(capacity));
+        if let Some(stem) = cache.get(word) {
+            return f(stem);
+        }
+        let stem = stemmer.stem(word).into_owned();
+        let result = f(&stem);
+        cache.put(word.to_string(), stem);
+        result
+    })
+}
+
+/// Harman's S-stemmer: only the first matching rule is applied
+///   ies → y (unless eies, aies), es → e (unless aes, ees, oes), s → (unless us, ss)
+fn s_stem(word: &str) -> Cow<'_, str> {
+    let len = word.len();
+    if len > 3 && word.ends_with("ies") && !word.ends_with("eies") && !word.ends_with("aies") {
+        return Cow::Owned(format!("{}y", &word[..len - 3]));
+    }
+    if len > 2 && word.ends_with("es") && !word.ends_with("aes") && !word.ends_with("ees") && !word.ends_with("oes") {
+        return Cow::Borrowed(&word[..len - 1]);
+    }
+    if len > 1 && word.ends_with('s') && !word.ends_with("us") && !word.ends_with("ss") {
+        return Cow::Borrowed(&word[..len - 1]);
+    }
+    Cow::Borrowed(word)
+}
+
+// Helpers shared by the English stemmers, following Porter's definitions
+// (y is a vowel after a consonant)
+
+fn is_consonant(word: &[u8], i: usize) -> bool {
+    match word[i] {
+        b'a' | b'e' | b'i' | b'o' | b'u' => false,
+        b'y' => i == 0 || !is_consonant(word, i - 1),
+        _ => true,
+    }
+}
+
+fn has_vowel(word: &[u8]) -> bool {
+    (0..word.len()).any(|i| !is_consonant(word, i))
+}
+
+/// Porter's measure: the number of vowel-consonant sequences
+fn measure(word: &[u8]) -> usize {
+    let mut m = 0;
+    let mut previous_vowel = false;
+    for i in 0..word.len() {
+        let vowel = !is_consonant(word, i);
+        if previous_vowel && !vowel {
+            m += 1;
+        }
+        previous_vowel = vowel;
+    }
+    m
+}
+
+/// Ends with consonant-vowel-consonant, the last one not w, x or y (hop, but not snow)
+fn ends_cvc(word: &[u8]) -> bool {
+    let n = word.len();
+    n >= 3
+        && is_consonant(word, n - 3)
+        && !is_consonant(word, n - 2)
+        && is_consonant(word, n - 1)
+        && !matches!(word[n - 1], b'w' | b'x' | b'y')
+}
@@ -0,0 +1,110 @@
+//! irregular.rs
+//!
+//! Provides the embedded English lexicon of the lemmatizer: irregular forms
+//! (went → go, children → child, better → good) and words that look inflected
+//! but are not (news, series, morning), which map to themselves.
+
+use phf::phf_map;
+
+/// Inflected form → lemma, every key is lowercase
+pub static IRREGULAR: phf::Map<&'static str, &'static str> = phf_map! {
+    "aches" => "ache", "acoustics" => "acoustics", "aerobics" => "aerobics", "afterwards" => "afterwards",
+    "aliases" => "alias", "alumni" => "alumnus", "always" => "always", "analyses" => "analysis",
+    "analysis" => "analysis", "anomaly" => "anomaly", "antennae" => "antenna", "anything" => "anything",
+    "apparatus" => "apparatus", "appendices" => "appendix", "arisen" => "arise", "arose" => "arise",
+    "assembly" => "assembly", "ate" => "eat", "athletics" => "athletics", "atlases" => "atlas",
+    "avalanches" => "avalanche", "awoke" => "awake", "awoken" => "awake", "axes" => "axis", "axis" => "axis",
+    "backwards" => "backwards", "bacteria" => "bacterium", "bases" => "basis", "basis" => "basis",
+    "beaten" => "beat", "became" => "become", "bed" => "bed", "been" => "be", "began" => "begin",
+    "begun" => "begin", "bent" => "bend", "besides" => "besides", "best" => "good", "better" => "good",
+    "biases" => "bias", "bitten" => "bite", "bled" => "bleed", "bleed" => "bleed", "blew" => "blow",
+    "blown" => "blow", "bonus" => "bonus", "bore" => "bear", "born" => "bear", "borne" => "bear",
+    "bought" => "buy", "bound" => "bind", "bred" => "breed", "breed" => "breed", "bring" => "bring",
+    "broke" => "break", "broken" => "break", "brought" => "bring", "brownies" => "brownie",
+    "built" => "build", "burnt" => "burn", "buses" => "bus", "business" => "business",
+    "butterfly" => "butterfly", "caches" => "cache", "cacti" => "cactus", "calories" => "calorie",
+    "calves" => "calf", "came" => "come", "campus" => "campus", "canoes" => "canoe", "canvases" => "canvas",
+    "caught" => "catch", "ceiling" => "ceiling", "census" => "census", "chaos" => "chaos",
+    "children" => "child", "chorus" => "chorus", "chose" => "choose", "chosen" => "choose",
+    "circus" => "circus", "cliches" => "cliche", "cling" => "cling", "clung" => "cling",
+    "consensus" => "consensus", "cookies" => "cookie", "corpus" => "corpus", "cosmos" => "cosmos",
+    "crept" => "creep", "crises" => "crisis",
+    "crisis" => "crisis", "criteria" => "criterion", "crooked" => "crooked", "curricula" => "curriculum",
+    "daily" => "daily", "dealt" => "deal", "diagnoses" => "diagnosis", "dice" => "die", "did" => "do",
+    "died" => "die", "dies" => "die", "does" => "do", "done" => "do", "downwards" => "downwards",
+    "drank" => "drink", "drawn" => "draw", "dreamt" => "dream", "drew" => "draw", "driven" => "drive",
+    "drove" => "drive", "drunk" => "drink", "dug" => "dig", "during" => "during", "dwelt" => "dwell",
+    "dyed" => "dye", "dying" => "die", "dynamics" => "dynamics", "eaten" => "eat", "economics" => "economics",
+    "elder" => "old", "eldest" => "old", "electronics" => "electronics", "elves" => "elf",
+    "emphases" => "emphasis", "ethics" => "ethics", "ethos" => "ethos", "evening" => "evening",
+    "everything" => "everything", "exceed" => "exceed", "fallen" => "fall", "family" => "family",
+    "farther" => "far", "farthest" => "far", "fed" => "feed", "feed" => "feed", "feet" => "foot",
+    "fell" => "fall", "felt" => "feel", "fled" => "flee", "flew" => "fly", "flies" => "fly",
+    "fling" => "fling", "flown" => "fly", "flung" => "fling", "foci" => "focus", "foes" => "foe",
+    "folly" => "folly", "forbade" => "forbid", "forbidden" => "forbid", "forgave" => "forgive",
+    "forgiven" => "forgive", "forgot" => "forget", "forgotten" => "forget", "formulae" => "formula",
+    "forwards" => "forwards", "fought" => "fight", "found" => "find", "freed" => "free", "froze" => "freeze",
+    "frozen" => "freeze", "fungi" => "fungus", "further" => "far", "furthest" => "far", "gases" => "gas",
+    "gave" => "give", "geese" => "goose", "genetics" => "genetics", "genies" => "genie", "given" => "give",
+    "goalies" => "goalie", "goes" => "go", "gone" => "go", "got" => "get", "gotten" => "get",
+    "graphics" => "graphics", "greed" => "greed", "grew" => "grow", "grown" => "grow",
+    "gymnastics" => "gymnastics", "had" => "have", "halves" => "half", "has" => "have", "having" => "have",
+    "headaches" => "headache", "heard" => "hear", "held" => "hold", "hers" => "hers", "hid" => "hide",
+    "hidden" => "hide", "hippies" => "hippie", "his" => "his", "hoes" => "hoe", "homily" => "homily",
+    "hooves" => "hoof", "hundred" => "hundred", "hung" => "hang", "hypotheses" => "hypothesis",
+    "indices" => "index", "iris" => "iris", "italy" => "italy", "its" => "its", "july" => "july",
+    "kept" => "keep", "king" => "king", "knelt" => "kneel", "knew" => "know", "knives" => "knife",
+    "known" => "know", "kudos" => "kudos", "laid" => "lay", "lain" => "lie", "larvae" => "larva",
+    "lay" => "lie", "leant" => "lean", "leapt" => "leap", "learnt" => "learn", "least" => "little",
+    "leaves" => "leaf", "led" => "lead", "lenses" => "lens", "lent" => "lend", "less" => "little",
+    "lice" => "louse", "lies" => "lie", "lily" => "lily", "linguistics" => "linguistics", "lives" => "life",
+    "loaves" => "loaf", "logistics" => "logistics", "lost" => "lose", "lying" => "lie", "made" => "make",
+    "mathematics" => "mathematics", "matrices" => "matrix", "means" => "means", "meant" => "mean",
+    "mechanics" => "mechanics", "melancholy" => "melancholy", "memoranda" => "memorandum", "men" => "man",
+    "met" => "meet", "mice" => "mouse", "minus" => "minus", "mistaken" => "mistake", "mistook" => "mistake",
+    "monopoly" => "monopoly", "more" => "many", "morning" => "morning", "most" => "many",
+    "moustaches" => "moustache", "movies" => "movie", "naked" => "naked", "need" => "need", "news" => "news",
+    "niches" => "niche", "nothing" => "nothing", "nuclei" => "nucleus", "oases" => "oasis", "oboes" => "oboe",
+    "octopus" => "octopus", "optics" => "optics", "ours" => "ours", "overcame" => "overcome", "oxen" => "ox",
+    "paid" => "pay", "parentheses" => "parenthesis", "pathos" => "pathos", "pelvis" => "pelvis",
+    "penis" => "penis", "people" => "person", "perhaps" => "perhaps", "phenomena" => "phenomenon",
+    "physics" => "physics", "pies" => "pie", "plus" => "plus", "politics" => "politics",
+    "prairies" => "prairie", "proceed" => "proceed", "procreated" => "procreate",
+    "procreating" => "procreate", "prospectus" => "prospectus", "proven" => "prove", "psyches" => "psyche",
+    "pudding" => "pudding", "quiches" => "quiche", "radii" => "radius", "ragged" => "ragged",
+    "rally" => "rally", "ran" => "run", "rang" => "ring", "red" => "red", "ridden" => "ride",
+    "ring" => "ring", "risen" => "rise", "rode" => "ride", "rookies" => "rookie", "rugged" => "rugged",
+    "rung" => "ring", "sacred" => "sacred", "said" => "say", "sang" => "sing", "sank" => "sink",
+    "sat" => "sit", "scarves" => "scarf", "seed" => "seed", "seen" => "see", "sees" => "see",
+    "selfies" => "selfie", "selves" => "self", "sent" => "send", "series" => "series", "sewn" => "sew",
+    "shaken" => "shake", "sheaves" => "sheaf", "shed" => "shed", "shelves" => "shelf", "shoes" => "shoe",
+    "shone" => "shine", "shook" => "shake", "shot" => "shoot", "shown" => "show", "shrank" => "shrink",
+    "shrunk" => "shrink", "sing" => "sing", "slain" => "slay", "sled" => "sled", "slept" => "sleep",
+    "slew" => "slay", "slid" => "slide", "sling" => "sling", "slung" => "sling", "smelt" => "smell",
+    "sold" => "sell", "something" => "something", "sometimes" => "sometimes", "sought" => "seek",
+    "spat" => "spit", "species" => "species", "sped" => "speed", "speed" => "speed", "spelt" => "spell",
+    "spent" => "spend", "spilt" => "spill", "spoilt" => "spoil", "spoke" => "speak", "spoken" => "speak",
+    "sprang" => "spring", "spring" => "spring", "sprung" => "spring", "spun" => "spin", "stank" => "stink",
+    "statistics" => "statistics", "status" => "status", "stimuli" => "stimulus", "sting" => "sting",
+    "stole" => "steal", "stolen" => "steal", "stood" => "stand", "strata" => "stratum",
+    "stricken" => "strike", "stridden" => "stride", "string" => "string", "striven" => "strive",
+    "strode" => "stride", "strove" => "strive", "struck" => "strike", "strung" => "string",
+    "stuck" => "stick", "stung" => "sting", "stunk" => "stink", "succeed" => "succeed", "sung" => "sing",
+    "sunk" => "sink", "surplus" => "surplus", "swam" => "swim", "swept" => "sweep", "swing" => "swing",
+    "swollen" => "swell", "swore" => "swear", "sworn" => "swear", "swum" => "swim", "swung" => "swing",
+    "syllabi" => "syllabus", "synopses" => "synopsis", "taken" => "take", "tally" => "tally",
+    "taught" => "teach", "teeth" => "tooth", "tennis" => "tennis", "theirs" => "theirs",
+    "thermos" => "thermos", "theses" => "thesis", "thesis" => "thesis", "thieves" => "thief",
+    "thing" => "thing", "this" => "this", "thought" => "think", "threw" => "throw", "thrown" => "throw",
+    "thus" => "thus", "tied" => "tie", "ties" => "tie", "toes" => "toe", "told" => "tell", "took" => "take",
+    "tore" => "tear", "torn" => "tear", "towards" => "towards", "trod" => "tread", "trodden" => "tread",
+    "tying" => "tie", "understood" => "understand", "undertaken" => "undertake", "undertook" => "undertake",
+    "upwards" => "upwards", "vertebrae" => "vertebra", "vertices" => "vertex", "virus" => "virus",
+    "vying" => "vie", "walrus" => "walrus", "was" => "be", "wed" => "wed", "wedding" => "wedding",
+    "weed" => "weed", "went" => "go", "wept" => "weep", "were" => "be", "wharves" => "wharf",
+    "whereas" => "whereas", "wicked" => "wicked", "wing" => "wing", "withdrawn" => "withdraw",
+    "withdrew" => "withdraw", "wives" => "wife", "woke" => "wake", "woken" => "wake", "wolves" => "wolf",
+    "women" => "woman", "won" => "win", "wore" => "wear", "worn" => "wear", "worse" => "bad",
+    "worst" => "bad", "wove" => "weave", "woven" => "weave", "written" => "write", "wrote" => "write",
+    "yes" => "yes", "yours" => "yours", "zombies" => "zombie",
+};
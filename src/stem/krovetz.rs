@@ -0,0 +1,68 @@
+//! krovetz.rs
+//!
+//! Provides a Krovetz-style light stemmer: inflections are undone like the
+//! lemmatizer does, then the most regular derivational endings (-ness, -ly)
+//! are removed. The original KStem checks every step against a large
+//! dictionary, which we do not ship, so the other derivational endings
+//! (-ment, -ity, -ion...) are left alone rather than guessed.
+
+use std::borrow::Cow;
+use super::irregular::IRREGULAR;
+use super::lemma::uninflect;
+use super::measure;
+
+pub fn stem(word: &str) -> Cow<'_, str> {
+    if let Some(lemma) = IRREGULAR.get(word) {
+        return Cow::Borrowed(lemma);
+    }
+    match uninflect(word) {
+        Cow::Borrowed(stem) => derive(stem),
+        Cow::Owned(stem) => Cow::Owned(derive(&stem).into_owned()),
+    }
+}
+
+fn derive(word: &str) -> Cow<'_, str> {
+    // happiness → happy, happily → happy
+    for suffix in ["iness", "ily"] {
+        if let Some(stem) = word.strip_suffix(suffix).filter(|s| measure(s.as_bytes()) > 0) {
+            return Cow::Owned(format!("{}y", stem));
+        }
+    }
+    // darkness → dark, but not business or witness
+    if let Some(stem) = word.strip_suffix("ness").filter(|s| s.len() > 3 && measure(s.as_bytes()) > 0) {
+        return Cow::Borrowed(stem);
+    }
+    // probably → probable, possibly → possible
+    if let Some(stem) = word.strip_suffix("bly").filter(|s| s.len() > 2) {
+        return Cow::Owned(format!("{}ble", stem));
+    }
+    // quickly → quick, totally → total, but not early, only, apply or reply
+    if let Some(stem) = word.strip_suffix("ly").filter(|s| s.len() > 3 && measure(s.as_bytes()) > 0 && !s.ends_with('p')) {
+        return Cow::Borrowed(stem);
+    }
+    Cow::Borrowed(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference_outputs() {
+        // Outputs of KStem (Krovetz, 1993) for words its dictionary does not override
+        let cases = [
+            // Inflections
+            ("dogs", "dog"), ("ponies", "pony"), ("boxes", "box"), ("agreed", "agree"),
+            ("walked", "walk"), ("hoping", "hope"), ("running", "run"), ("children", "child"),
+            // -ness and -ly
+            ("darkness", "dark"), ("happiness", "happy"), ("happily", "happy"), ("quickly", "quick"),
+            ("probably", "probable"), ("possibly", "possible"),
+            // Left alone
+            ("business", "business"), ("witness", "witness"), ("early", "early"), ("only", "only"),
+            ("apply", "apply"), ("reply", "reply"), ("government", "government"), ("nation", "nation"),
+        ];
+        for (word, expected) in cases {
+            assert_eq!(stem(word), expected, "{}", word);
+        }
+    }
+}
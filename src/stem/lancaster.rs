@@ -0,0 +1,193 @@
+//! lancaster.rs
+//!
+//! Provides the Lancaster (Paice/Husk) stemmer, the most aggressive of the lot.
+//! Rules are tried in order on the end of the word and applied repeatedly
+//! until one says stop, none matches, or the stem would become too short.
+
+use std::borrow::Cow;
+
+struct Rule {
+    ending: &'static str,
+    /// Only applies to a word no other rule has touched yet
+    intact: bool,
+    remove: usize,
+    append: &'static str,
+    stop: bool,
+}
+
+/// The standard Paice/Husk rule table
+static RULES: &[Rule] = &[
+    Rule { ending: "ia", intact: true, remove: 2, append: "", stop: true },
+    Rule { ending: "a", intact: true, remove: 1, append: "", stop: true },
+    Rule { ending: "bb", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "ytic", intact: false, remove: 3, append: "s", stop: true },
+    Rule { ending: "ic", intact: false, remove: 2, append: "", stop: false },
+    Rule { ending: "nc", intact: false, remove: 1, append: "t", stop: false },
+    Rule { ending: "dd", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "ied", intact: false, remove: 3, append: "y", stop: false },
+    Rule { ending: "ceed", intact: false, remove: 2, append: "ss", stop: true },
+    Rule { ending: "eed", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "ed", intact: false, remove: 2, append: "", stop: false },
+    Rule { ending: "hood", intact: false, remove: 4, append: "", stop: false },
+    Rule { ending: "e", intact: false, remove: 1, append: "", stop: false },
+    Rule { ending: "lief", intact: false, remove: 1, append: "v", stop: true },
+    Rule { ending: "if", intact: false, remove: 2, append: "", stop: false },
+    Rule { ending: "ing", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "iag", intact: false, remove: 3, append: "y", stop: true },
+    Rule { ending: "ag", intact: false, remove: 2, append: "", stop: false },
+    Rule { ending: "gg", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "th", intact: true, remove: 2, append: "", stop: true },
+    Rule { ending: "guish", intact: false, remove: 5, append: "ct", stop: true },
+    Rule { ending: "ish", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "i", intact: true, remove: 1, append: "", stop: true },
+    Rule { ending: "i", intact: false, remove: 1, append: "y", stop: false },
+    Rule { ending: "ij", intact: false, remove: 1, append: "d", stop: true },
+    Rule { ending: "fuj", intact: false, remove: 1, append: "s", stop: true },
+    Rule { ending: "uj", intact: false, remove: 1, append: "d", stop: true },
+    Rule { ending: "oj", intact: false, remove: 1, append: "d", stop: true },
+    Rule { ending: "hej", intact: false, remove: 1, append: "r", stop: true },
+    Rule { ending: "verj", intact: false, remove: 1, append: "t", stop: true },
+    Rule { ending: "misj", intact: false, remove: 2, append: "t", stop: true },
+    Rule { ending: "nj", intact: false, remove: 1, append: "d", stop: true },
+    Rule { ending: "j", intact: false, remove: 1, append: "s", stop: true },
+    Rule { ending: "ifiabl", intact: false, remove: 6, append: "", stop: true },
+    Rule { ending: "iabl", intact: false, remove: 4, append: "y", stop: true },
+    Rule { ending: "abl", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "ibl", intact: false, remove: 3, append: "", stop: true },
+    Rule { ending: "bil", intact: false, remove: 2, append: "l", stop: false },
+    Rule { ending: "cl", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "iful", intact: false, remove: 4, append: "y", stop: true },
+    Rule { ending: "ful", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "ul", intact: false, remove: 2, append: "", stop: true },
+    Rule { ending: "ial", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "ual", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "al", intact: false, remove: 2, append: "", stop: false },
+    Rule { ending: "ll", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "ium", intact: false, remove: 3, append: "", stop: true },
+    Rule { ending: "um", intact: true, remove: 2, append: "", stop: true },
+    Rule { ending: "ism", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "mm", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "sion", intact: false, remove: 4, append: "j", stop: false },
+    Rule { ending: "xion", intact: false, remove: 4, append: "ct", stop: true },
+    Rule { ending: "ion", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "ian", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "an", intact: false, remove: 2, append: "", stop: false },
+    Rule { ending: "een", intact: false, remove: 0, append: "", stop: true },
+    Rule { ending: "en", intact: false, remove: 2, append: "", stop: false },
+    Rule { ending: "nn", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "ship", intact: false, remove: 4, append: "", stop: false },
+    Rule { ending: "pp", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "er", intact: false, remove: 2, append: "", stop: false },
+    Rule { ending: "ear", intact: false, remove: 0, append: "", stop: true },
+    Rule { ending: "ar", intact: false, remove: 2, append: "", stop: true },
+    Rule { ending: "or", intact: false, remove: 2, append: "", stop: false },
+    Rule { ending: "ur", intact: false, remove: 2, append: "", stop: false },
+    Rule { ending: "rr", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "tr", intact: false, remove: 1, append: "", stop: false },
+    Rule { ending: "ier", intact: false, remove: 3, append: "y", stop: false },
+    Rule { ending: "ies", intact: false, remove: 3, append: "y", stop: false },
+    Rule { ending: "sis", intact: false, remove: 2, append: "", stop: true },
+    Rule { ending: "is", intact: false, remove: 2, append: "", stop: false },
+    Rule { ending: "ness", intact: false, remove: 4, append: "", stop: false },
+    Rule { ending: "ss", intact: false, remove: 0, append: "", stop: true },
+    Rule { ending: "ous", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "us", intact: true, remove: 2, append: "", stop: true },
+    Rule { ending: "s", intact: true, remove: 1, append: "", stop: false },
+    Rule { ending: "s", intact: false, remove: 0, append: "", stop: true },
+    Rule { ending: "plicat", intact: false, remove: 4, append: "y", stop: true },
+    Rule { ending: "at", intact: false, remove: 2, append: "", stop: false },
+    Rule { ending: "ment", intact: false, remove: 4, append: "", stop: false },
+    Rule { ending: "ent", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "ant", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "ript", intact: false, remove: 2, append: "b", stop: true },
+    Rule { ending: "orpt", intact: false, remove: 2, append: "b", stop: true },
+    Rule { ending: "duct", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "sumpt", intact: false, remove: 2, append: "", stop: true },
+    Rule { ending: "cept", intact: false, remove: 2, append: "iv", stop: true },
+    Rule { ending: "olut", intact: false, remove: 2, append: "v", stop: true },
+    Rule { ending: "sist", intact: false, remove: 0, append: "", stop: true },
+    Rule { ending: "ist", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "tt", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "iqu", intact: false, remove: 3, append: "", stop: true },
+    Rule { ending: "ogu", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "siv", intact: false, remove: 3, append: "j", stop: false },
+    Rule { ending: "eiv", intact: false, remove: 0, append: "", stop: true },
+    Rule { ending: "iv", intact: false, remove: 2, append: "", stop: false },
+    Rule { ending: "bly", intact: false, remove: 1, append: "", stop: false },
+    Rule { ending: "ily", intact: false, remove: 3, append: "y", stop: false },
+    Rule { ending: "ply", intact: false, remove: 0, append: "", stop: true },
+    Rule { ending: "ly", intact: false, remove: 2, append: "", stop: false },
+    Rule { ending: "ogy", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "phy", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "omy", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "opy", intact: false, remove: 1, append: "", stop: true },
+    Rule { ending: "ity", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "ety", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "lty", intact: false, remove: 2, append: "", stop: true },
+    Rule { ending: "istry", intact: false, remove: 5, append: "", stop: true },
+    Rule { ending: "ary", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "ory", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "ify", intact: false, remove: 3, append: "", stop: true },
+    Rule { ending: "ncy", intact: false, remove: 2, append: "t", stop: false },
+    Rule { ending: "acy", intact: false, remove: 3, append: "", stop: false },
+    Rule { ending: "iz", intact: false, remove: 2, append: "", stop: false },
+    Rule { ending: "yz", intact: false, remove: 1, append: "s", stop: true },
+];
+
+pub fn stem(word: &str) -> Cow<'_, str> {
+    let mut stem = Cow::Borrowed(word);
+    let mut intact = true;
+    while let Some(rule) = RULES.iter().find(|rule| {
+        stem.ends_with(rule.ending) && (intact || !rule.intact) && is_acceptable(stem.as_bytes(), rule.remove)
+    }) {
+        let kept = stem.len() - rule.remove;
+        stem = match stem {
+            Cow::Borrowed(borrowed) if rule.append.is_empty() => Cow::Borrowed(&borrowed[..kept]),
+            owned => Cow::Owned(format!("{}{}", &owned[..kept], rule.append)),
+        };
+        intact = false;
+        if rule.stop {
+            break;
+        }
+    }
+    stem
+}
+
+/// A stem starting with a vowel keeps at least 2 letters, otherwise at least 3 with a vowel among them
+fn is_acceptable(word: &[u8], remove: usize) -> bool {
+    let Some(len) = word.len().checked_sub(remove) else {
+        return false;
+    };
+    let is_vowel = |b: u8| b"aeiouy".contains(&b);
+    if is_vowel(word[0]) {
+        len >= 2
+    } else {
+        len >= 3 && (is_vowel(word[1]) || is_vowel(word[2]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference_outputs() {
+        // The examples of NLTK's LancasterStemmer, which implements the same rule table
+        let cases = [
+            ("maximum", "maxim"),
+            ("presumably", "presum"),
+            ("multiply", "multiply"),
+            ("provision", "provid"),
+            ("owed", "ow"),
+            ("ear", "ear"),
+            ("saying", "say"),
+            ("crying", "cry"),
+            ("string", "string"),
+            ("meant", "meant"),
+            ("cement", "cem"),
+        ];
+        for (word, expected) in cases {
+            assert_eq!(stem(word), expected, "{}", word);
+        }
+    }
+}
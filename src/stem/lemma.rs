@@ -0,0 +1,137 @@
+//! lemma.rs
+//!
+//! Provides a dictionary-based English lemmatizer: irregular forms and false
+//! inflections come from the embedded lexicon, regular inflections (plurals,
+//! -ed, -ing, -iest) are undone by rule, restoring the silent e
+//! (created → create, hoping → hope) and undoubling consonants (running → run).
+//! There is no part-of-speech tagging, so derivational endings are left alone.
+
+use std::borrow::Cow;
+use super::irregular::IRREGULAR;
+use super::{ends_cvc, has_vowel, is_consonant, measure};
+
+pub fn lemmatize(word: &str) -> Cow<'_, str> {
+    if let Some(lemma) = IRREGULAR.get(word) {
+        return Cow::Borrowed(lemma);
+    }
+    uninflect(word)
+}
+
+/// Undoes regular inflections, words shorter than 4 letters are left alone
+pub(super) fn uninflect(word: &str) -> Cow<'_, str> {
+    let len = word.len();
+    if len < 4 {
+        return Cow::Borrowed(word);
+    }
+
+    if let Some(stem) = word.strip_suffix("ies").filter(|_| len > 4) {
+        return Cow::Owned(format!("{}y", stem));
+    }
+    if let Some(stem) = word.strip_suffix("iest").filter(|_| len > 5) {
+        return Cow::Owned(format!("{}y", stem));
+    }
+    if ["ss", "us", "is"].iter().any(|s| word.ends_with(s)) {
+        return Cow::Borrowed(word);
+    }
+    // boxes, churches, wishes, buzzes, classes, heroes
+    if ["xes", "ches", "shes", "zzes", "sses", "oes"].iter().any(|s| word.ends_with(s)) {
+        return Cow::Borrowed(&word[..len - 2]);
+    }
+    if let Some(stem) = word.strip_suffix('s') {
+        return Cow::Borrowed(stem);
+    }
+
+    if let Some(stem) = word.strip_suffix("eed") {
+        // agreed → agree, but need and speed stay
+        return match measure(stem.as_bytes()) > 0 {
+            true => Cow::Borrowed(&word[..len - 1]),
+            false => Cow::Borrowed(word),
+        };
+    }
+    if let Some(stem) = word.strip_suffix("ied") {
+        return Cow::Owned(format!("{}y", stem));
+    }
+    if let Some(stem) = word.strip_suffix("ed").or_else(|| word.strip_suffix("ing")) {
+        return restore(word, stem);
+    }
+    Cow::Borrowed(word)
+}
+
+/// Repairs a stem left by removing -ed or -ing
+fn restore<'a>(word: &'a str, stem: &'a str) -> Cow<'a, str> {
+    let bytes = stem.as_bytes();
+    let n = bytes.len();
+    // bring, string, red: there was no ending to begin with
+    if n < 2 || !has_vowel(bytes) {
+        return Cow::Borrowed(word);
+    }
+
+    // running → run, but falling, missing, buzzing and add keep their double letter
+    if n > 3 && bytes[n - 1] == bytes[n - 2] && is_consonant(bytes, n - 1) && !b"lsz".contains(&bytes[n - 1]) {
+        return Cow::Borrowed(&stem[..n - 1]);
+    }
+    if needs_e(bytes) {
+        return Cow::Owned(format!("{}e", stem));
+    }
+    Cow::Borrowed(stem)
+}
+
+/// Whether a stem lost a silent e along with its ending
+fn needs_e(stem: &[u8]) -> bool {
+    let n = stem.len();
+    let last = stem[n - 1];
+    let before = stem[n - 2];
+    let vowel = |i: usize| !is_consonant(stem, i);
+
+    // relate, evaluate, organize (but not eat, float or treat), enable, handle (but not curl or howl)
+    // In create and recreate, unlike treat and repeat, the e and the a are separate vowels
+    if stem.ends_with(b"at") && n >= 3 && !b"aeo".contains(&stem[n - 3]) || stem.ends_with(b"creat") || stem.ends_with(b"iz") {
+        return true;
+    }
+    if last == b'l' && is_consonant(stem, n - 2) && !b"lrw".contains(&before) {
+        return true;
+    }
+    // love, dance, argue, judge, charge: English words rarely end in these
+    if matches!(last, b'v' | b'c' | b'u') || stem.ends_with(b"dg") || stem.ends_with(b"rg") {
+        return true;
+    }
+    // cause, please, choose, freeze
+    if n >= 3 && matches!(last, b's' | b'z') && vowel(n - 2) && vowel(n - 3) {
+        return true;
+    }
+    // use, age, owe
+    if n == 2 && vowel(0) && is_consonant(stem, 1) && !matches!(last, b'w' | b'x' | b'y') {
+        return true;
+    }
+    // hope, make, write
+    measure(stem) == 1 && ends_cvc(stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference_outputs() {
+        // Lemmas given by WordNet's morphy, for the part of speech the word is inflected as
+        let cases = [
+            // Irregular forms
+            ("went", "go"), ("children", "child"), ("better", "good"), ("criteria", "criterion"),
+            ("analyses", "analysis"), ("cacti", "cactus"), ("bought", "buy"),
+            // Plurals
+            ("dogs", "dog"), ("studies", "study"), ("boxes", "box"), ("churches", "church"),
+            ("wishes", "wish"), ("classes", "class"), ("heroes", "hero"),
+            // Past tenses and participles
+            ("agreed", "agree"), ("created", "create"), ("recreating", "recreate"), ("treated", "treat"),
+            ("repeating", "repeat"), ("floated", "float"), ("hoping", "hope"), ("running", "run"),
+            ("walked", "walk"), ("falling", "fall"), ("studied", "study"), ("loved", "love"),
+            ("judging", "judge"), ("caused", "cause"), ("handled", "handle"), ("organizing", "organize"),
+            // Words that are not inflected
+            ("news", "news"), ("series", "series"), ("morning", "morning"), ("bring", "bring"),
+            ("need", "need"), ("glass", "glass"), ("bus", "bus"), ("analysis", "analysis"),
+        ];
+        for (word, expected) in cases {
+            assert_eq!(lemmatize(word), expected, "{}", word);
+        }
+    }
+}
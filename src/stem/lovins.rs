@@ -0,0 +1,203 @@
+//! lovins.rs
+//!
+//! Provides the Lovins (1968) stemmer: the longest ending whose condition
+//! holds is removed, doubled consonants are undoubled, then the end of the
+//! stem is respelled (absorpt → absorb) so that variants meet.
+
+use std::borrow::Cow;
+
+/// Conditions on the stem left after removing an ending
+#[derive(Clone, Copy)]
+enum Condition {
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, AA, BB, CC,
+}
+
+impl Condition {
+    fn holds(self, stem: &[u8]) -> bool {
+        use Condition::*;
+        let n = stem.len();
+        let ends = |suffix: &str| stem.ends_with(suffix.as_bytes());
+        // u*e: an 'e' two letters after a 'u'
+        let uxe = || n >= 3 && stem[n - 1] == b'e' && stem[n - 3] == b'u';
+        match self {
+            A => n >= 2,
+            B => n >= 3,
+            C => n >= 4,
+            D => n >= 5,
+            E => n >= 2 && !ends("e"),
+            F => n >= 3 && !ends("e"),
+            G => n >= 3 && ends("f"),
+            H => n >= 2 && (ends("t") || ends("ll")),
+            I => n >= 2 && !ends("o") && !ends("e"),
+            J => n >= 2 && !ends("a") && !ends("e"),
+            K => n >= 3 && (ends("l") || ends("i") || uxe()),
+            L => n >= 2 && !ends("u") && !ends("x") && (!ends("s") || ends("os")),
+            M => n >= 2 && !ends("a") && !ends("c") && !ends("e") && !ends("m"),
+            N => n >= 3 && (stem[n - 3] != b's' || n >= 4),
+            O => n >= 2 && (ends("l") || ends("i")),
+            P => n >= 2 && !ends("c"),
+            Q => n >= 3 && !ends("l") && !ends("n"),
+            R => n >= 2 && (ends("n") || ends("r")),
+            S => n >= 2 && (ends("dr") || (ends("t") && !ends("tt"))),
+            T => n >= 2 && (ends("s") || (ends("t") && !ends("ot"))),
+            U => n >= 2 && (ends("l") || ends("m") || ends("n") || ends("r")),
+            V => n >= 2 && ends("c"),
+            W => n >= 2 && !ends("s") && !ends("u"),
+            X => n >= 2 && (ends("l") || ends("i") || uxe()),
+            Y => n >= 2 && ends("in"),
+            Z => n >= 2 && !ends("f"),
+            AA => n >= 2 && ["d", "f", "ph", "th", "l", "er", "or", "es", "t"].iter().any(|s| ends(s)),
+            BB => n >= 3 && !ends("met") && !ends("ryst"),
+            CC => n >= 2 && ends("l"),
+        }
+    }
+}
+
+use Condition::*;
+
+/// The 294 endings, longest first
+static ENDINGS: &[(&str, Condition)] = &[
+    ("alistically", B), ("arizability", A), ("izationally", B),
+    ("antialness", A), ("arisations", A), ("arizations", A), ("entialness", A),
+    ("allically", C), ("antaneous", A), ("antiality", A), ("arisation", A),
+    ("arization", A), ("ationally", B), ("ativeness", A), ("eableness", E),
+    ("entations", A), ("entiality", A), ("entialize", A), ("entiation", A),
+    ("ionalness", A), ("istically", A), ("itousness", A), ("izability", A),
+    ("izational", A),
+    ("ableness", A), ("arizable", A), ("entation", A), ("entially", A),
+    ("eousness", A), ("ibleness", A), ("icalness", A), ("ionalism", A),
+    ("ionality", A), ("ionalize", A), ("iousness", A), ("izations", A),
+    ("lessness", A),
+    ("ability", A), ("aically", A), ("alistic", B), ("alities", A),
+    ("ariness", E), ("aristic", A), ("arizing", A), ("ateness", A),
+    ("atingly", A), ("ational", B), ("atively", A), ("ativism", A),
+    ("elihood", E), ("encible", A), ("entally", A), ("entials", A),
+    ("entiate", A), ("entness", A), ("fulness", A), ("ibility", A),
+    ("icalism", A), ("icalist", A), ("icality", A), ("icalize", A),
+    ("ication", G), ("icianry", A), ("ination", A), ("ingness", A),
+    ("ionally", A), ("isation", A), ("ishness", A), ("istical", A),
+    ("iteness", A), ("iveness", A), ("ivistic", A), ("ivities", A),
+    ("ization", F), ("izement", A), ("oidally", A), ("ousness", A),
+    ("aceous", A), ("acious", B), ("action", G), ("alness", A),
+    ("ancial", A), ("ancies", A), ("ancing", B), ("ariser", A),
+    ("arized", A), ("arizer", A), ("atable", A), ("ations", B),
+    ("atives", A), ("eature", Z), ("efully", A), ("encies", A),
+    ("encing", A), ("ential", A), ("enting", C), ("entist", A),
+    ("eously", A), ("ialist", A), ("iality", A), ("ialize", A),
+    ("ically", A), ("icance", A), ("icians", A), ("icists", A),
+    ("ifully", A), ("ionals", A), ("ionate", D), ("ioning", A),
+    ("ionist", A), ("iously", A), ("istics", A), ("izable", E),
+    ("lessly", A), ("nesses", A), ("oidism", A),
+    ("acies", A), ("acity", A), ("aging", B), ("aical", A),
+    ("alist", A), ("alism", B), ("ality", A), ("alize", A),
+    ("allic", BB), ("anced", B), ("ances", B), ("antic", C),
+    ("arial", A), ("aries", A), ("arily", A), ("arity", B),
+    ("arize", A), ("aroid", A), ("ately", A), ("ating", I),
+    ("ation", B), ("ative", A), ("ators", A), ("atory", A),
+    ("ature", E), ("early", Y), ("ehood", A), ("eless", A),
+    ("elity", A), ("ement", A), ("enced", A), ("ences", A),
+    ("eness", E), ("ening", E), ("ental", A), ("ented", C),
+    ("ently", A), ("fully", A), ("ially", A), ("icant", A),
+    ("ician", A), ("icide", A), ("icism", A), ("icist", A),
+    ("icity", A), ("idine", I), ("iedly", A), ("ihood", A),
+    ("inate", A), ("iness", A), ("ingly", B), ("inism", J),
+    ("inity", CC), ("ional", A), ("ioned", A), ("ished", A),
+    ("istic", A), ("ities", A), ("itous", A), ("ively", A),
+    ("ivity", A), ("izers", F), ("izing", F), ("oidal", A),
+    ("oides", A), ("otide", A), ("ously", A),
+    ("able", A), ("ably", A), ("ages", B), ("ally", B),
+    ("ance", B), ("ancy", B), ("ants", B), ("aric", A),
+    ("arly", K), ("ated", I), ("ates", A), ("atic", B),
+    ("ator", A), ("ealy", Y), ("edly", E), ("eful", A),
+    ("eity", A), ("ence", A), ("ency", A), ("ened", E),
+    ("enly", E), ("eous", A), ("hood", A), ("ials", A),
+    ("ians", A), ("ible", A), ("ibly", A), ("ical", A),
+    ("ides", L), ("iers", A), ("iful", A), ("ines", M),
+    ("ings", N), ("ions", B), ("ious", A), ("isms", B),
+    ("ists", A), ("itic", H), ("ized", F), ("izer", F),
+    ("less", A), ("lily", A), ("ness", A), ("ogen", A),
+    ("ward", A), ("wise", A), ("ying", B), ("yish", A),
+    ("acy", A), ("age", B), ("aic", A), ("als", BB),
+    ("ant", B), ("ars", O), ("ary", F), ("ata", A),
+    ("ate", A), ("eal", Y), ("ear", Y), ("ely", E),
+    ("ene", E), ("ent", C), ("ery", E), ("ese", A),
+    ("ful", A), ("ial", A), ("ian", A), ("ics", A),
+    ("ide", L), ("ied", A), ("ier", A), ("ies", P),
+    ("ily", A), ("ine", M), ("ing", N), ("ion", Q),
+    ("ish", C), ("ism", B), ("ist", A), ("ite", AA),
+    ("ity", A), ("ium", A), ("ive", A), ("ize", F),
+    ("oid", A), ("one", R), ("ous", A),
+    ("ae", A), ("al", BB), ("ar", X), ("as", B),
+    ("ed", E), ("en", F), ("es", E), ("ia", A),
+    ("ic", A), ("is", A), ("ly", B), ("on", S),
+    ("or", T), ("um", U), ("us", V), ("yl", R),
+    ("a", A), ("e", A), ("i", A), ("o", A),
+    ("s", W), ("y", B),
+];
+
+/// Respelling rules: ending, replacement and the letters that may not precede it, longest first
+static RESPELLINGS: &[(&str, &str, &str)] = &[
+    ("umpt", "um", ""), ("istr", "ister", ""), ("metr", "meter", ""), ("erid", "eris", ""),
+    ("pand", "pans", ""),
+    ("iev", "ief", ""), ("uct", "uc", ""), ("rpt", "rb", ""), ("urs", "ur", ""),
+    ("olv", "olut", ""), ("bex", "bic", ""), ("dex", "dic", ""), ("pex", "pic", ""),
+    ("tex", "tic", ""), ("lux", "luc", ""), ("uad", "uas", ""), ("vad", "vas", ""),
+    ("cid", "cis", ""), ("lid", "lis", ""), ("end", "ens", "s"), ("ond", "ons", ""),
+    ("lud", "lus", ""), ("rud", "rus", ""), ("her", "hes", "pt"), ("mit", "mis", ""),
+    ("ent", "ens", "m"), ("ert", "ers", ""),
+    ("ul", "l", "aio"), ("ax", "ac", ""), ("ex", "ec", ""), ("ix", "ic", ""),
+    ("et", "es", "n"), ("yt", "ys", ""), ("yz", "ys", ""),
+];
+
+pub fn stem(word: &str) -> Cow<'_, str> {
+    let bytes = word.as_bytes();
+    let ending = ENDINGS
+        .iter()
+        .find(|(ending, condition)| {
+            bytes.len() > ending.len()
+                && word.ends_with(ending)
+                && condition.holds(&bytes[..bytes.len() - ending.len()])
+        })
+        .map_or(0, |(ending, _)| ending.len());
+    let mut stem = &word[..word.len() - ending];
+
+    // Undouble: bb, dd, gg, ll, mm, nn, pp, rr, ss, tt
+    let s = stem.as_bytes();
+    if s.len() >= 2 && s[s.len() - 1] == s[s.len() - 2] && b"bdglmnprst".contains(&s[s.len() - 1]) {
+        stem = &stem[..stem.len() - 1];
+    }
+
+    match respell(stem) {
+        Some(respelled) => Cow::Owned(respelled),
+        None if stem.len() == word.len() => Cow::Borrowed(word),
+        None => Cow::Borrowed(stem),
+    }
+}
+
+fn respell(stem: &str) -> Option<String> {
+    RESPELLINGS.iter().find_map(|(ending, replacement, excluded)| {
+        let prefix = stem.strip_suffix(ending)?;
+        if prefix.bytes().last().is_some_and(|b| excluded.as_bytes().contains(&b)) {
+            return None;
+        }
+        Some(format!("{}{}", prefix, replacement))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference_outputs() {
+        // Manning, Raghavan & Schütze, Introduction to Information Retrieval, figure 2.8
+        let text = "such an analysis can reveal features that are not easily visible from the variations \
+            in the individual genes and can lead to a picture of expression that is more biologically \
+            transparent and accessible to interpretation";
+        let expected = "such an analys can reve featur that ar not eas vis from th vari \
+            in th individu gen and can lead to a pictur of expres that is mor biolog \
+            transpar and acces to interpres";
+        let stems: Vec<String> = text.split_whitespace().map(|word| stem(word).into_owned()).collect();
+        assert_eq!(stems.join(" "), expected.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+}
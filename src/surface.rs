@@ -0,0 +1,52 @@
+//! surface.rs
+//!
+//! Provides the stem → surface form statistics written next to normalized
+//! files, so that stems ("punctuat", "sampl") can be shown to humans as the
+//! word that most often produced them ("punctuation", "sample").
+
+use std::fs::File;
+use std::io::{BufWriter, Result, Write};
+use std::path::Path;
+use crate::counts::Counter;
+
+#[derive(Default)]
+pub struct SurfaceForms {
+    /// Stem → surface form → occurrences
+    counts: Counter<Counter<u64>>,
+}
+
+impl SurfaceForms {
+    /// Counts one occurrence of a word (lowercased, before stemming) and its stem
+    pub fn add(&mut self, stem: &str, word: &str) {
+        self.counts.update(stem, |words| words.update(word, |count| *count += 1));
+    }
+
+    pub fn merge(self, other: SurfaceForms) -> SurfaceForms {
+        SurfaceForms {
+            counts: self.counts.merge(other.counts, |words, other| {
+                *words = std::mem::take(words).merge(other, |count, other| *count += other);
+            }),
+        }
+    }
+
+    /// Writes a `stem<TAB>word` TSV sorted by stem, keeping the most frequent word of each stem
+    /// (ties go to the alphabetically first word so that the output is deterministic)
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let mut stems: Vec<(&String, &String)> = self.counts
+            .iter()
+            .filter_map(|(stem, words)| {
+                words
+                    .iter()
+                    .max_by(|(a, ca), (b, cb)| ca.cmp(cb).then_with(|| b.cmp(a)))
+                    .map(|(word, _)| (stem, word))
+            })
+            .collect();
+        stems.sort_unstable();
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        for (stem, word) in stems {
+            writeln!(writer, "{}\t{}", stem, word)?;
+        }
+        writer.flush()
+    }
+}
@@ -0,0 +1,136 @@
+//! synonym.rs
+//!
+//! Provides the synonym table: tokens mapped to a canonical form
+//! ("colour" → "color", "usa" → "us") before stemming, so that query-side and
+//! index-side normalization collapse equivalent terms identically.
+//! The table is a TSV file with one `term<TAB>canonical` pair per line.
+//!
+//! Terms that are a single word are looked up on the token stream, after
+//! stopword removal. Terms with punctuation or spaces ("u.s.", "united states")
+//! would be shredded by the tokenizer, so they are matched on the raw text instead.
+//! Canonical forms are keyed like the terms, one token per word, so that
+//! "usa<TAB>United States" yields the tokens "united" and "states".
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Result};
+use std::path::Path;
+use crate::pipeline::Pipeline;
+use crate::protect::ProtectedTokens;
+use crate::token::Token;
+
+pub struct Synonyms {
+    /// Single-word terms, keyed the way the tokenizer outputs them, and the words of their canonical form
+    words: HashMap<String, Vec<String>>,
+    /// Whether some canonical form has several words, so that mapping may add tokens
+    expands: bool,
+    /// Multiword and punctuated terms, matched before tokenization
+    /// Their canonical forms are written with single spaces between the words
+    pub phrases: Option<ProtectedTokens>,
+}
+
+impl Synonyms {
+    /// Loads the TSV table, keying single words like the pipeline's tokenizer would
+    pub fn load(path: &Path, pipeline: &Pipeline) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        Self::parse(&contents, path, |word| pipeline.token_key(word))
+    }
+
+    fn parse(contents: &str, path: &Path, key: impl Fn(&str) -> String) -> Result<Self> {
+        let mut words = HashMap::new();
+        let mut phrases = HashMap::new();
+        for (n, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((term, canonical)) = line.split_once('\t') else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}:{}: expected term<TAB>canonical", path.display(), n + 1),
+                ));
+            };
+            let term = term.trim();
+            let canonical: Vec<String> = canonical
+                .split_whitespace()
+                .map(&key)
+                .filter(|word| !word.is_empty())
+                .collect();
+
+            if term.chars().all(char::is_alphanumeric) {
+                words.insert(key(term), canonical);
+            } else {
+                phrases.insert(term.to_string(), canonical.join(" "));
+            }
+        }
+
+        let expands = words.values().any(|canonical| canonical.len() != 1);
+        Ok(Synonyms { words, expands, phrases: ProtectedTokens::canonical(&phrases, true)? })
+    }
+
+    /// Replaces every word with its canonical form
+    pub fn map(&self, tokens: &mut Vec<Token>) {
+        if self.words.is_empty() {
+            return;
+        }
+
+        // One word for one word: replace in place
+        if !self.expands {
+            for token in tokens.iter_mut().filter(|t| t.is_word()) {
+                if let Some(canonical) = self.words.get(&token.text) {
+                    token.text.clone_from(&canonical[0]);
+                }
+            }
+            return;
+        }
+
+        let mut mapped = Vec::with_capacity(tokens.len());
+        for token in tokens.drain(..) {
+            match self.words.get(&token.text).filter(|_| token.is_word()) {
+                Some(canonical) => mapped.extend(canonical.iter().cloned().map(Token::word)),
+                None => mapped.push(token),
+            }
+        }
+        *tokens = mapped;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::Piece;
+
+    fn synonyms(table: &str) -> Synonyms {
+        Synonyms::parse(table, Path::new("synonyms.tsv"), |word| word.to_lowercase()).unwrap()
+    }
+
+    fn words(texts: &[&str]) -> Vec<Token> {
+        texts.iter().map(|text| Token::word(text.to_string())).collect()
+    }
+
+    #[test]
+    fn canonical_forms_are_keyed_like_terms() {
+        let synonyms = synonyms("colour\tColor\nusa\tUnited States\n");
+        let mut tokens = words(&["colour", "usa", "flag"]);
+        synonyms.map(&mut tokens);
+        let texts: Vec<&str> = tokens.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(texts, ["color", "united", "states", "flag"]);
+        assert!(tokens.iter().all(Token::is_word));
+    }
+
+    #[test]
+    fn single_word_canonical_forms_replace_in_place() {
+        let synonyms = synonyms("Colour\tCOLOR\n");
+        let mut tokens = words(&["colour", "red"]);
+        synonyms.map(&mut tokens);
+        assert_eq!(tokens[0].text, "color");
+        assert_eq!(tokens.len(), 2);
+    }
+
+    #[test]
+    fn phrase_canonical_forms_are_keyed_like_terms() {
+        let synonyms = synonyms("u.s.\tUnited   States\n");
+        let pieces = synonyms.phrases.as_ref().unwrap().split("the U.S. flag");
+        assert!(matches!(pieces[1], Piece::Canonical("united states")));
+    }
+}
@@ -0,0 +1,46 @@
+//! token.rs
+//!
+//! Provides the token stream between the tokenizer and the output string.
+//! Stages that run after tokenization (phrase merging, stemming...) work
+//! on a `Vec<Token>` instead of re-splitting text.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    /// A lowercased (or case folded) word that survived the filters, stemmed in place later on
+    Word,
+    /// Emitted as-is: protected terms, entity placeholders and merged phrases
+    Verbatim,
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub text: String,
+    pub kind: TokenKind,
+}
+
+impl Token {
+    pub fn word(text: String) -> Self {
+        Token { text, kind: TokenKind::Word }
+    }
+
+    pub fn verbatim(text: String) -> Self {
+        Token { text, kind: TokenKind::Verbatim }
+    }
+
+    pub fn is_word(&self) -> bool {
+        self.kind == TokenKind::Word
+    }
+}
+
+/// Joins the tokens with single spaces
+pub fn join(tokens: &[Token]) -> String {
+    let len = tokens.iter().map(|t| t.text.len() + 1).sum();
+    let mut joined = String::with_capacity(len);
+    for token in tokens {
+        if !joined.is_empty() {
+            joined.push(' ');
+        }
+        joined.push_str(&token.text);
+    }
+    joined
+}
@@ -0,0 +1,125 @@
+//! unicode.rs
+//!
+//! Provides the Unicode preprocessing steps that run before tokenization:
+//! normalization forms, diacritic stripping and transliteration to ASCII.
+//! Each step borrows the input when it has nothing to do.
+
+use std::borrow::Cow;
+use std::io::Result;
+use deunicode::deunicode_with_tofu_cow;
+use unicode_normalization::{is_nfc_quick, is_nfd_quick, is_nfkc_quick, is_nfkd_quick, IsNormalized, UnicodeNormalization};
+use unidecode::unidecode;
+use crate::pipeline::invalid_input;
+
+/// Unicode normalization forms (UAX #15)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Form {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+impl Form {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "nfc" => Ok(Form::Nfc),
+            "nfd" => Ok(Form::Nfd),
+            "nfkc" => Ok(Form::Nfkc),
+            "nfkd" => Ok(Form::Nfkd),
+            _ => Err(invalid_input(format!("Unknown unicode form: {}", name))),
+        }
+    }
+}
+
+/// Backend used to turn text into ASCII in ascii mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transliterator {
+    /// Higher fidelity, always allocates
+    Unidecode,
+    /// Faster, borrows ASCII input as-is
+    Deunicode,
+}
+
+impl Transliterator {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "unidecode" => Ok(Transliterator::Unidecode),
+            "deunicode" => Ok(Transliterator::Deunicode),
+            _ => Err(invalid_input(format!("Unknown transliterator: {}", name))),
+        }
+    }
+
+    pub fn transliterate<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self {
+            Transliterator::Unidecode => Cow::Owned(unidecode(text)),
+            // Unknown characters become separators, like unidecode's empty replacements
+            Transliterator::Deunicode => deunicode_with_tofu_cow(text, " "),
+        }
+    }
+}
+
+/// Applies a normalization form, skipping the work when the quick check
+/// already proves the text is in that form
+pub fn normalize_form(text: &str, form: Form) -> Cow<'_, str> {
+    let quick = match form {
+        Form::Nfc => is_nfc_quick(text.chars()),
+        Form::Nfd => is_nfd_quick(text.chars()),
+        Form::Nfkc => is_nfkc_quick(text.chars()),
+        Form::Nfkd => is_nfkd_quick(text.chars()),
+    };
+    if quick == IsNormalized::Yes {
+        return Cow::Borrowed(text);
+    }
+
+    match form {
+        Form::Nfc => Cow::Owned(text.nfc().collect()),
+        Form::Nfd => Cow::Owned(text.nfd().collect()),
+        Form::Nfkc => Cow::Owned(text.nfkc().collect()),
+        Form::Nfkd => Cow::Owned(text.nfkd().collect()),
+    }
+}
+
+/// Removes diacritics only (café → cafe, naïve → naive)
+/// Letters without a decomposition (ß, ø, Cyrillic, CJK) are left untouched
+/// and the result is recomposed to NFC
+pub fn strip_diacritics(text: &str) -> Cow<'_, str> {
+    // ASCII has nothing to strip
+    if text.is_ascii() {
+        return Cow::Borrowed(text);
+    }
+
+    Cow::Owned(text.nfd().filter(|&c| !is_diacritic(c)).nfc().collect())
+}
+
+/// Marks of the Combining Diacritical Marks blocks
+/// Other combining marks (Indic vowel signs and viramas, kana voicing marks)
+/// are part of the spelling and are kept
+fn is_diacritic(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE20}'..='\u{FE2F}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_latin_diacritics() {
+        assert_eq!(strip_diacritics("café naïve Ångström"), "cafe naive Angstrom");
+        assert_eq!(strip_diacritics("ß ø"), "ß ø");
+    }
+
+    #[test]
+    fn keeps_marks_of_other_scripts() {
+        // Vowel signs and viramas (Devanagari), voicing marks (kana)
+        assert_eq!(strip_diacritics("हिन्दी"), "हिन्दी");
+        assert_eq!(strip_diacritics("が ぱ ガ"), "が ぱ ガ");
+        assert_eq!(strip_diacritics("हिन्दी が café"), "हिन्दी が cafe");
+    }
+}
@@ -0,0 +1,81 @@
+//! vocab.rs
+//!
+//! Provides the corpus vocabulary written next to normalized files: the term
+//! frequency and document frequency of every output token.
+//! The file also serves as the IDF table of the feature hasher, which needs
+//! the number of documents: the totals go to a `<file>.totals` sidecar.
+
+use std::fs::File;
+use std::io::{BufWriter, Result, Write};
+use std::path::{Path, PathBuf};
+use crate::counts::Counter;
+
+#[derive(Default)]
+pub struct Vocabulary {
+    /// Token → (term frequency, document frequency)
+    counts: Counter<(u64, u64)>,
+    /// Totals over every document, including the empty ones
+    tokens: u64,
+    documents: u64,
+}
+
+impl Vocabulary {
+    /// Counts the tokens of a normalized text (tokens separated by single spaces)
+    pub fn add_document(&mut self, text: &str) {
+        self.documents += 1;
+        if text.is_empty() {
+            return;
+        }
+
+        // Sorting groups the occurrences of each token, so that it counts once towards its document frequency
+        let mut tokens: Vec<&str> = text.split(' ').collect();
+        tokens.sort_unstable();
+        self.tokens += tokens.len() as u64;
+        for run in tokens.chunk_by(|a, b| a == b) {
+            self.counts.update(run[0], |(tf, df)| {
+                *tf += run.len() as u64;
+                *df += 1;
+            });
+        }
+    }
+
+    pub fn merge(self, other: Vocabulary) -> Vocabulary {
+        Vocabulary {
+            counts: self.counts.merge(other.counts, |(tf, df), (other_tf, other_df)| {
+                *tf += other_tf;
+                *df += other_df;
+            }),
+            tokens: self.tokens + other.tokens,
+            documents: self.documents + other.documents,
+        }
+    }
+
+    /// Writes a `token<TAB>term frequency<TAB>document frequency` TSV, most frequent first
+    /// (ties in alphabetical order), leaving out tokens seen less than `min_count` times
+    /// The totals go to `<path>.totals`, as `tokens<TAB>n` and `documents<TAB>n` lines
+    pub fn write(&self, path: &Path, min_count: u64) -> Result<()> {
+        let mut tokens: Vec<(&String, &(u64, u64))> = self.counts
+            .iter()
+            .filter(|(_, (tf, _))| *tf >= min_count)
+            .collect();
+        tokens.sort_unstable_by(|(a, (ta, _)), (b, (tb, _))| tb.cmp(ta).then_with(|| a.cmp(b)));
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        for (token, (tf, df)) in tokens {
+            writeln!(writer, "{}\t{}\t{}", token, tf, df)?;
+        }
+        writer.flush()?;
+
+        let mut totals = BufWriter::new(File::create(totals_path(path))?);
+        writeln!(totals, "tokens\t{}", self.tokens)?;
+        writeln!(totals, "documents\t{}", self.documents)?;
+        totals.flush()
+    }
+}
+
+/// Path of the totals sidecar of a vocabulary file
+pub fn totals_path(path: &Path) -> PathBuf {
+    let mut totals = path.as_os_str().to_owned();
+    totals.push(".totals");
+    PathBuf::from(totals)
+}
@@ -1,11 +1,15 @@
+from .config import NormalizerConfig
 from .core import (
     normalize_text,
     normalize_jsonl_file,
     normalize_jsonl_files,
+    find_simhash_pairs,
 )
 
 __all__ = [
+    "NormalizerConfig",
     "normalize_text",
     "normalize_jsonl_file",
     "normalize_jsonl_files",
+    "find_simhash_pairs",
 ]
\ No newline at end of file
@@ -0,0 +1,250 @@
+from dataclasses import dataclass, field, fields
+from typing import Dict, List, Optional, Tuple, Union
+
+@dataclass
+class NormalizerConfig:
+    """
+    Configuration for the normalization pipeline.
+    The defaults reproduce the original sstn behaviour for space-delimited scripts,
+    except that the last word of a text is kept (it used to be dropped unless
+    followed by punctuation or whitespace).
+
+    Args:
+        strip_html (bool): Strip markup before anything else. Tags and comments are removed,
+            <script> and <style> blocks are dropped with their content and HTML entities
+            (&amp;, &nbsp;, &#8217;...) are decoded.
+        mode (str): How the text is split into words.
+            "ascii" transliterates the text to ASCII and splits on non-alphanumeric characters.
+            "unicode" keeps letters from any script, splits on Unicode word boundaries (UAX #29)
+            and applies full Unicode case folding.
+        language (str): Language used to select the stemmer and stopword list
+            (e.g. "english", "french", "russian" or their ISO 639-1 codes).
+            Stopwords are only removed for languages that have a bundled list.
+        stemmer (str): How words are reduced. "snowball" (Porter2 for English) is the default and
+            supports every language. The others are English only: "none" keeps words as-is, "s" only
+            removes plurals, "lovins" and "lancaster" are aggressive stemmers, "krovetz" undoes
+            inflections plus -ness and -ly, and "lemmatizer" maps words to their dictionary form
+            (went → go, studies → study, better → good) using an embedded lexicon.
+            With `auto_language`, documents in other languages fall back to Snowball.
+        stem_exceptions (list or dict): Words the stemmer must not touch. A list keeps each word as-is
+            (["news", "physics"]), a dict maps each word to the stem emitted instead
+            ({"better": "good", "news": "news"}). Words and stems are lowercased, so the
+            exceptions apply whatever the case in the text.
+        stem_cache_size (int): Number of stems each worker thread keeps in an LRU cache, so that frequent
+            words are stemmed once instead of once per occurrence. This pays off for the slower stemmers
+            (lovins), Snowball and the lemmatizer are faster without it. 0 disables the cache.
+        surface_forms (bool): When normalizing files, count the words that produced each stem and
+            write `<output_file>.surface.tsv` next to the output, a `stem<TAB>word` table mapping
+            each stem to its most frequent word (punctuat → punctuation), to make stems readable.
+        vocabulary (bool): When normalizing files, count the term frequency and document frequency of
+            every output token and write a `token<TAB>tf<TAB>df` table sorted by frequency
+            (`<output_file>.vocab.tsv`, or `vocab.tsv` in the output directory for all the files).
+            The totals (every token, every document) go to a `<vocabulary file>.totals` sidecar.
+            Documents dropped by the quality filters or deduplication are not counted.
+            The file can be used as the `idf_file` of a later run.
+        vocabulary_min_count (int): Tokens occurring fewer times are left out of the vocabulary.
+        unicode_form (str, optional): Unicode normalization form applied before anything else,
+            one of "nfc", "nfd", "nfkc" or "nfkd". None leaves the text as-is.
+        strip_diacritics (bool): Remove diacritics only (café → cafe) while keeping
+            the rest of the text in its original script. Combining marks that are part
+            of the spelling (Indic vowel signs, kana voicing marks) are kept.
+        transliterator (str): Backend used to convert text to ASCII in "ascii" mode.
+            "unidecode" has higher fidelity, "deunicode" is faster.
+        rewrite_rules (list): Ordered (pattern, replacement) regex rules applied with find/replace-all,
+            e.g. [(r"(?s)Subscribe to our newsletter.*", ""), (r"utm_\\w+=\\w+", " ")].
+            Replacements can reference groups ($1, ${name}). Patterns use the Rust `regex` syntax,
+            which has no lookaround or backreferences.
+        rewrite_stage (str): Where the rules are applied. "raw" runs them on the document as received,
+            before any other stage. "ascii" runs them on the transliterated text right before it is
+            split into words (only in "ascii" mode).
+        min_token_length (int): Words shorter than this (in characters) are dropped.
+            Words in scripts written without spaces (CJK, Thai...) are exempt.
+        max_token_length (int, optional): Words longer than this are dropped, which gets rid of
+            base64 blobs, hashes and other garbage. The whole word is checked, before identifier
+            splitting. None keeps every word.
+        numbers (str): What to do with words made only of digits. "drop" removes them,
+            "keep" keeps them (subject to the length filters), "placeholder" replaces each one
+            with `number_placeholder`.
+        number_placeholder (str): Token emitted for numbers when `numbers` is "placeholder".
+        drop_alphanumeric (bool): Drop words mixing letters and digits (a1b2c3, 5GHz).
+            The whole word is checked, before identifier splitting.
+        split_camel_case (bool): Split on lower → upper transitions (camelCase → camel case).
+        split_acronyms (bool): Split the end of an acronym from the next word
+            (HTTPServer → http server, XMLHttpRequest → xml http request).
+        split_digits (bool): Split on letter ↔ digit transitions (mp3player → mp 3 player).
+        split_snake_case (bool): Split on underscores and hyphens (get_user_id → get user id).
+            In "ascii" mode punctuation is always a separator, so this only matters in "unicode" mode.
+        segmentation (str): Dictionary-based word segmentation for scripts written without spaces
+            (Chinese, Japanese, Thai, Lao, Khmer, Myanmar). "auto" segments documents containing
+            those scripts, "always" segments every document and "off" disables it.
+            In "ascii" mode each segmented word is romanized as a whole and never stemmed.
+            The dictionaries are bundled, no download is needed.
+        auto_language (bool): Detect the language of each document and use its stemmer and
+            stopwords, falling back to `language` when detection is unreliable or the detected
+            language has no stemmer.
+        dedup (str): Exact deduplication when normalizing files, based on a 128-bit xxh3 hash of the
+            normalized text (so documents differing only by case, punctuation or stopwords match).
+            "drop" leaves repeats out of the output, "flag" keeps them and writes the first occurrence
+            to `duplicate_field`, "off" disables it. Repeats are found within and across the files of a
+            single call, and clusters of duplicates are written to a `hash<TAB>size<TAB>location...` report
+            (`<output_file>.duplicates.tsv`, or `duplicates.tsv` in the output directory).
+            Locations are `path:line`, the line of the input file counted from 1 (blank and invalid lines
+            included). Only hashes are kept in memory.
+            Records left empty by normalization are never considered duplicates.
+        duplicate_field (str): Field receiving the `path:line` location of the first occurrence of a
+            repeated document in "flag" mode (null for first occurrences and records without text).
+        hash_field (str, optional): When normalizing files, write the hash of the normalized text
+            (32 hex digits) to this field of each record. Records whose normalized text is missing
+            or empty get null there, as in the other fingerprint and duplicate fields.
+        dedup_method (str): What makes two documents duplicates for `dedup`. "exact" compares the
+            hashes of their normalized texts, "minhash" puts them in the same LSH cluster (near duplicates,
+            requires `minhash_permutations` and `lsh_bands`). With "minhash" the report is keyed by cluster ID.
+        minhash_permutations (int): Number of MinHash permutations computed over word shingles of the
+            normalized text when normalizing files. 0 disables MinHash.
+        shingle_size (int): Words per shingle. Documents shorter than that are a single shingle.
+        minhash_seed (int): Seed of the permutations. Signatures are only comparable with the same seed,
+            permutations and shingle size.
+        lsh_bands (int): Split signatures into this many bands (it must divide `minhash_permutations`)
+            and cluster documents sharing any band, across all the files of a call. More bands find
+            less similar documents. Clusters are assigned in input order, a document joins the cluster of
+            the earliest document it matches. 0 disables clustering.
+        minhash_field (str, optional): Field receiving the MinHash signature (a list of integers).
+        cluster_field (str, optional): Field receiving the LSH cluster ID, numbered from 0 in order
+            of first appearance (requires `lsh_bands`).
+        simhash_field (str, optional): Field receiving a 64-bit SimHash of the normalized words
+            (16 hex digits), for lightweight near-duplicate detection with `find_simhash_pairs`.
+        quality_filters (dict): Gopher/C4-style quality thresholds applied when normalizing files, as
+            `min_<metric>` or `max_<metric>` → value, e.g. {"min_words": 50, "max_mean_word_length": 10}.
+            Metrics are measured on the text before normalization (after `strip_html`):
+            "words" (whitespace-separated), "mean_word_length" (characters), "symbol_ratio" ("#" and
+            ellipses per word), "stopword_ratio" (in the stopword list of the document language),
+            "punctuated_lines" (fraction of non-empty lines ending in terminal punctuation),
+            "top_<n>gram_fraction" (characters in the most frequent word n-gram) and
+            "duplicate_<n>gram_fraction" (characters in word n-grams occurring more than once).
+            Undefined metrics (no words, no stopword list) pass.
+        quality_action (str): "drop" leaves documents failing a filter out of the output (and out of
+            deduplication), "annotate" keeps them and requires `quality_field`.
+        quality_field (str, optional): Field receiving the score of each filtered metric, plus a "pass" flag.
+        language_field (str, optional): When normalizing files, write the detected language
+            (ISO 639-3 code, e.g. "eng", "cmn", "und" if unknown) to this field of each record.
+        language_confidence_field (str, optional): When normalizing files, write the detection
+            confidence (0 to 1) to this field of each record.
+        entities (dict): Entities recognized on the raw text before tokenization, mapped to the
+            placeholder token that replaces them (None drops them). Supported kinds are "url",
+            "email", "mention", "hashtag", "ip", "date" and "number",
+            e.g. {"url": "<url>", "email": "<email>", "mention": None}.
+        protected_tokens (list or dict): Terms that bypass splitting, length filters, stopwords and
+            stemming ("C++", ".NET", "COVID-19", "US"). A list keeps each term verbatim, a dict maps
+            each term to the canonical form that is emitted instead (e.g. {"C#": "csharp"}).
+            Matching happens on the raw text and only on whole words.
+        protected_case_insensitive (bool): Match protected terms ignoring ASCII case.
+        synonyms_file (str, optional): TSV file with one `term<TAB>canonical` pair per line
+            ("colour\tcolor", "u.s.\tus"). Terms are replaced by their canonical form before stemming,
+            after stopword removal. Terms with punctuation or spaces are matched on the raw text,
+            ignoring ASCII case. Canonical forms are lowercased like the text, and a canonical
+            form of several words ("usa\tunited states") yields one token per word.
+        phrases_file (str, optional): File with one multiword expression per line ("new york",
+            "machine learning"), merged into single tokens after stopword removal. Phrases go
+            through the same pipeline as the text, so they match regardless of case or stopwords.
+        phrase_stage (str): "before_stemming" merges surface words (new_york),
+            "after_stemming" merges stems (machin_learn).
+        phrase_separator (str): Joins the words of a merged phrase.
+        ngram_range (tuple): Smallest and largest n of the word n-grams built from the final tokens
+            (after stemming and phrase merging), e.g. (1, 3) for unigrams, bigrams and trigrams.
+            (1, 1) leaves the tokens as they are.
+        ngram_separator (str): Joins the tokens of an n-gram (machin_learn_model).
+        ngram_field (str, optional): When normalizing files, write the n-grams to this array field and
+            keep the normalized text as-is. None replaces the text with the space-separated n-grams
+            (which is also what `normalize_text` returns).
+        hashing_features (int): When normalizing files, map the tokens of each document's normalized text
+            to this many buckets with a seeded hash (the hashing trick) and emit the resulting sparse
+            vector. 0 disables it.
+        hashing_seed (int): Seed of the bucket hash. Vectors are only comparable with the same seed
+            and number of features.
+        hashing_weights (str): "count" weights each bucket by the number of tokens it received,
+            "tfidf" weights each token by its smoothed IDF, ln((1 + N) / (1 + df)) + 1, read from `idf_file`.
+        idf_file (str, optional): Vocabulary file written by an earlier run with `vocabulary=True`,
+            required for "tfidf" weights. Tokens missing from it get the IDF of a document frequency of 0.
+            The number of documents N is read from its `.totals` sidecar; without one, the largest
+            document frequency in the file is used.
+        hashing_normalize (bool): Scale each vector to unit (L2) length.
+        hashing_field (str, optional): Field receiving the vector as a list of [bucket, weight] pairs,
+            sorted by bucket.
+        hashing_file (bool): Write the vectors to `<output_file>.vectors.bin`, one per output record in order:
+            the number of entries (uint32), then each bucket (uint32) and weight (float32), little-endian.
+            Records without text get an empty vector, so that rows line up with the output.
+    """
+    strip_html : bool = False
+    mode : str = "ascii"
+    language : str = "english"
+    stemmer : str = "snowball"
+    stem_exceptions : Union[List[str], Dict[str, str]] = field(default_factory=list)
+    stem_cache_size : int = 0
+    surface_forms : bool = False
+    vocabulary : bool = False
+    vocabulary_min_count : int = 1
+    unicode_form : Optional[str] = None
+    strip_diacritics : bool = False
+    transliterator : str = "unidecode"
+    rewrite_rules : List[Tuple[str, str]] = field(default_factory=list)
+    rewrite_stage : str = "raw"
+    min_token_length : int = 2
+    max_token_length : Optional[int] = None
+    numbers : str = "drop"
+    number_placeholder : str = "<num>"
+    drop_alphanumeric : bool = False
+    split_camel_case : bool = True
+    split_acronyms : bool = False
+    split_digits : bool = False
+    split_snake_case : bool = False
+    segmentation : str = "auto"
+    auto_language : bool = False
+    dedup : str = "off"
+    duplicate_field : str = "duplicate_of"
+    hash_field : Optional[str] = None
+    dedup_method : str = "exact"
+    minhash_permutations : int = 0
+    shingle_size : int = 5
+    minhash_seed : int = 0
+    lsh_bands : int = 0
+    minhash_field : Optional[str] = None
+    cluster_field : Optional[str] = None
+    simhash_field : Optional[str] = None
+    quality_filters : Dict[str, float] = field(default_factory=dict)
+    quality_action : str = "drop"
+    quality_field : Optional[str] = None
+    language_field : Optional[str] = None
+    language_confidence_field : Optional[str] = None
+    entities : Dict[str, Optional[str]] = field(default_factory=dict)
+    protected_tokens : Union[List[str], Dict[str, str]] = field(default_factory=list)
+    protected_case_insensitive : bool = False
+    synonyms_file : Optional[str] = None
+    phrases_file : Optional[str] = None
+    phrase_stage : str = "before_stemming"
+    phrase_separator : str = "_"
+    ngram_range : Tuple[int, int] = (1, 1)
+    ngram_separator : str = "_"
+    ngram_field : Optional[str] = None
+    hashing_features : int = 0
+    hashing_seed : int = 0
+    hashing_weights : str = "count"
+    idf_file : Optional[str] = None
+    hashing_normalize : bool = False
+    hashing_field : Optional[str] = None
+    hashing_file : bool = False
+
+def _config_dict(config : Optional[NormalizerConfig]) -> dict:
+    # The rust side receives a plain dict with every field filled in
+    config = config if config is not None else NormalizerConfig()
+    # A shallow copy, asdict deep copies every list and dict on each call and the rust side only reads them
+    as_dict = {f.name: getattr(config, f.name) for f in fields(config)}
+
+    # A plain list of protected terms keeps each one verbatim
+    if not isinstance(config.protected_tokens, dict):
+        as_dict["protected_tokens"] = {term: term for term in config.protected_tokens}
+    if not isinstance(config.stem_exceptions, dict):
+        as_dict["stem_exceptions"] = {word: word for word in config.stem_exceptions}
+    # Rules may come as lists (e.g. loaded from JSON)
+    as_dict["rewrite_rules"] = [tuple(rule) for rule in config.rewrite_rules]
+    as_dict["ngram_range"] = tuple(config.ngram_range)
+    return as_dict
@@ -1,26 +1,30 @@
 import os
-from typing import Union
-from sstn._norm import __normalize_text, __normalize_jsonl_file
+from typing import Optional, Union
+from sstn._norm import __normalize_text, __normalize_jsonl_files, __find_simhash_pairs
+from sstn.config import NormalizerConfig, _config_dict
 
 def normalize_text(
     text : str,
+    config : Optional[NormalizerConfig] = None,
 ) -> str:
     """
     Normalize the text by removing special characters and converting to lowercase.
     
     Args:
         text (str): The input text to be normalized.
+        config (NormalizerConfig, optional): Pipeline configuration. Defaults to NormalizerConfig().
     
     Returns:
         str: The normalized text.
     """
-    return __normalize_text(text) # Call internal rust function
+    return __normalize_text(text, _config_dict(config)) # Call internal rust function
 
 def normalize_jsonl_file(
     input_file : Union[str, os.PathLike],
     output_file : Union[str, os.PathLike],
     text_column : str = "text",
     workers : int = 1,
+    config : Optional[NormalizerConfig] = None,
 ) -> None:
     """
     Normalize a JSONL file by applying text normalization to each line.
@@ -28,14 +32,18 @@ def normalize_jsonl_file(
     Args:
         input_file (str): The path to the input JSONL file.
         output_file (str): The path to the output JSONL file.
+        config (NormalizerConfig, optional): Pipeline configuration. Defaults to NormalizerConfig().
     """
-    __normalize_jsonl_file(input_file, output_file, text_column, workers) # Call internal rust function
+    # Reports (duplicates, vocabulary) are written next to the output file
+    report_prefix = f"{os.fspath(output_file)}."
+    __normalize_jsonl_files([os.fspath(input_file)], [os.fspath(output_file)], text_column, workers, _config_dict(config), report_prefix) # Call internal rust function
 
 def normalize_jsonl_files(
     paths : list[Union[str, os.PathLike]],
     output_dir : Union[str, os.PathLike],
     text_column : str = "text",
     workers : int = 1,
+    config : Optional[NormalizerConfig] = None,
 ) -> None:
     """
     Normalize multiple JSONL files by applying text normalization to each line."
@@ -43,9 +51,12 @@ def normalize_jsonl_files(
     Args:
         paths (list): A list of paths to the input JSONL files.
         output_dir (str): The directory where the output JSONL files will be saved.
+        config (NormalizerConfig, optional): Pipeline configuration. Defaults to NormalizerConfig().
 
     Information:
         The output files will be named the same as the input files, but in a different directory.
+        With `config.dedup`, repeats are detected across all the files and reported in `duplicates.tsv`.
+        With `config.vocabulary`, a single `vocab.tsv` covers all the files.
     """
 
     path_map = {
@@ -62,5 +73,35 @@ def normalize_jsonl_files(
         if not os.path.isfile(path):
             raise IsADirectoryError(f"Path {path} is not a file.")
     
-    for path in paths:
-        __normalize_jsonl_file(path, path_map[path], text_column, workers)
\ No newline at end of file
+    # A single call, so that duplicates and the vocabulary span all the files
+    report_prefix = os.path.join(output_dir, "")
+    __normalize_jsonl_files(
+        [os.fspath(path) for path in paths],
+        [os.fspath(path_map[path]) for path in paths],
+        text_column,
+        workers,
+        _config_dict(config),
+        report_prefix,
+    )
+
+def find_simhash_pairs(
+    paths : list[Union[str, os.PathLike]],
+    output_file : Union[str, os.PathLike],
+    field : str = "simhash",
+    max_distance : int = 3,
+) -> int:
+    """
+    Find pairs of documents whose SimHash fingerprints differ by at most `max_distance` bits.
+    The files are expected to have been normalized with `NormalizerConfig(simhash_field=...)`.
+
+    Args:
+        paths (list): The JSONL files to search, pairs are found within and across them.
+        output_file (str): Where to write the pairs, one `location<TAB>location<TAB>distance` line each.
+            Locations are `path:line`, lines of the input file counted from 1.
+        field (str): The field holding the fingerprints. Records without one are skipped.
+        max_distance (int): The maximum Hamming distance, below 64. Small values (3 or less) are much faster.
+
+    Returns:
+        int: The number of pairs found.
+    """
+    return __find_simhash_pairs([os.fspath(path) for path in paths], os.fspath(output_file), field, max_distance)